@@ -0,0 +1,442 @@
+//! Interactive terminal browser of test results. Left pane lists tests (filtered by verdict),
+//! right pane shows details of the selected one: side-by-side diff, stderr, valgrind report
+//! and compilation warnings. Selected test can be run again or its files opened in an editor.
+
+use std::env;
+use std::io::{self, Write};
+use std::process::Command;
+
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+use termion::{clear, color, cursor, style};
+
+use crate::language::LiteralGenerator;
+use r_test::settings::Options;
+use r_test::testing::{self, TestResult};
+
+/// Width of the list of tests (with the separator)
+const LIST_WIDTH: u16 = 32;
+
+/// Editor used to open files of a test, when $EDITOR is not set
+const DEFAULT_EDITOR: &str = "less";
+
+/// Which tests are shown on the list
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    All,
+    Passed,
+    Failed,
+    Flaky,
+    Diff,
+    Valgrind,
+}
+
+impl Filter {
+    fn next(self) -> Filter {
+        match self {
+            Filter::All => Filter::Passed,
+            Filter::Passed => Filter::Failed,
+            Filter::Failed => Filter::Flaky,
+            Filter::Flaky => Filter::Diff,
+            Filter::Diff => Filter::Valgrind,
+            Filter::Valgrind => Filter::All,
+        }
+    }
+
+    fn matches(self, result: &TestResult) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Passed => result.passed(),
+            Filter::Failed => !result.passed(),
+            Filter::Flaky => result.flaky(),
+            Filter::Diff => result.diff_error(),
+            Filter::Valgrind => result.valgrind_error(),
+        }
+    }
+
+    fn literal(self) -> &'static str {
+        match self {
+            Filter::All => "BROWSER_FILTER_ALL",
+            Filter::Passed => "BROWSER_FILTER_PASSED",
+            Filter::Failed => "BROWSER_FILTER_FAILED",
+            Filter::Flaky => "BROWSER_FILTER_FLAKY",
+            Filter::Diff => "BROWSER_FILTER_DIFF",
+            Filter::Valgrind => "BROWSER_FILTER_VALGRIND",
+        }
+    }
+}
+
+/// Part of details of the selected test shown in the right pane
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Diff,
+    Stderr,
+    Valgrind,
+    Warnings,
+}
+
+const SECTIONS: [Section; 4] = [
+    Section::Diff,
+    Section::Stderr,
+    Section::Valgrind,
+    Section::Warnings,
+];
+
+impl Section {
+    fn next(self) -> Section {
+        let position = SECTIONS
+            .iter()
+            .position(|section| *section == self)
+            .unwrap();
+        SECTIONS[(position + 1) % SECTIONS.len()]
+    }
+
+    fn literal(self) -> &'static str {
+        match self {
+            Section::Diff => "BROWSER_SECTION_DIFF",
+            Section::Stderr => "BROWSER_SECTION_STDERR",
+            Section::Valgrind => "BROWSER_SECTION_VALGRIND",
+            Section::Warnings => "BROWSER_SECTION_WARNINGS",
+        }
+    }
+}
+
+/// Cuts text to given number of characters, or pads it with spaces to that number
+fn fit(text: &str, width: usize) -> String {
+    let text = text.replace('\t', "    ");
+    let mut fitted: String = text.chars().take(width).collect();
+    let length = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - length));
+    fitted
+}
+
+struct Browser<'a, T: LiteralGenerator> {
+    results: &'a mut Vec<TestResult>,
+    settings: &'a Options,
+    lang: &'a T,
+    filter: Filter,
+    section: Section,
+    /// Position of the selected test among the shown ones
+    selected: usize,
+    /// First shown test on the list
+    list_offset: usize,
+    /// First shown line of the section
+    scroll: usize,
+    /// Lines of the section, None when they have to be prepared again
+    lines: Option<Vec<String>>,
+    status: String,
+}
+
+impl<'a, T: LiteralGenerator> Browser<'a, T> {
+    /// Returns indexes of results shown on the list
+    fn shown(&self) -> Vec<usize> {
+        (0..self.results.len())
+            .filter(|index| self.filter.matches(&self.results[*index]))
+            .collect()
+    }
+
+    fn selected_result(&self) -> Option<usize> {
+        self.shown().get(self.selected).copied()
+    }
+
+    /// Prepares lines of the chosen section of the selected test
+    fn section_lines(&self, width: usize) -> Vec<String> {
+        let result = match self.selected_result() {
+            Some(index) => &self.results[index],
+            None => return Vec::new(),
+        };
+        let none = self.lang.get_literal("BROWSER_NOTHING").to_string();
+
+        let text = match self.section {
+            Section::Diff => result.side_by_side_diff(width).unwrap_or(none),
+            Section::Stderr => result
+                .get_program_stderr()
+                .map(str::to_string)
+                .unwrap_or(none),
+            Section::Valgrind if result.valgrind_error() => result.get_problem().to_string(),
+            Section::Valgrind => none,
+            Section::Warnings => result
+                .get_compilation_warnings()
+                .map(str::to_string)
+                .unwrap_or(none),
+        };
+        text.lines().map(str::to_string).collect()
+    }
+
+    fn move_selection(&mut self, shift: isize) {
+        let count = self.shown().len();
+        if count == 0 {
+            return;
+        }
+        let selected = self.selected as isize + shift;
+        self.selected = selected.clamp(0, count as isize - 1) as usize;
+        self.scroll = 0;
+        self.lines = None;
+    }
+
+    fn draw<W: Write>(&mut self, screen: &mut W) -> io::Result<()> {
+        let (width, height) = termion::terminal_size()?;
+        let list_width = LIST_WIDTH.min(width / 3) as usize;
+        let detail_width = (width as usize).saturating_sub(list_width + 1);
+        let height = height as usize;
+        // Title row, status row and help row are not used by the list
+        let list_height = height.saturating_sub(3).max(1);
+
+        let shown = self.shown();
+        if self.selected >= shown.len() {
+            self.selected = shown.len().saturating_sub(1);
+        }
+        if self.selected < self.list_offset {
+            self.list_offset = self.selected;
+        } else if self.selected >= self.list_offset + list_height {
+            self.list_offset = self.selected + 1 - list_height;
+        }
+        if self.lines.is_none() {
+            self.lines = Some(self.section_lines(detail_width));
+        }
+
+        let mut left = vec![format!(
+            "{} {} ({})",
+            self.lang.get_literal("BROWSER_FILTER"),
+            self.lang.get_literal(self.filter.literal()),
+            shown.len()
+        )];
+        for (position, index) in shown.iter().enumerate().skip(self.list_offset) {
+            let result = &self.results[*index];
+            let (mark, mark_color) = if result.flaky() {
+                ("~", color::Fg(color::Yellow).to_string())
+            } else if result.passed() {
+                ("+", color::Fg(color::Green).to_string())
+            } else {
+                ("-", color::Fg(color::Red).to_string())
+            };
+            let name = fit(&result.get_name(), list_width.saturating_sub(2));
+            if position == self.selected {
+                left.push(format!(
+                    "{}{} {}{}",
+                    style::Invert,
+                    mark,
+                    name,
+                    style::Reset
+                ));
+            } else {
+                left.push(format!(
+                    "{}{}{} {}",
+                    mark_color,
+                    mark,
+                    color::Fg(color::Reset),
+                    name
+                ));
+            }
+        }
+
+        let mut right = Vec::new();
+        if let Some(index) = self.selected_result() {
+            let result = &self.results[index];
+            let verdict = if result.flaky() {
+                self.lang.get_literal("RESULT_FLAKY_OUT")
+            } else if result.passed() {
+                self.lang.get_literal("RESULT_TRUE_OUT")
+            } else {
+                self.lang.get_literal("RESULT_FALSE_OUT")
+            };
+            let problem = if result.passed() {
+                String::new()
+            } else {
+                format!(" - {}", result.get_problem_description())
+            };
+            right.push(fit(
+                &format!("{}: {}{}", result.get_name(), verdict, problem),
+                detail_width,
+            ));
+
+            let memory = match result.get_usage() {
+                Some(usage) => format!("{} kB", usage.max_rss_kb),
+                None => "-".to_string(),
+            };
+            right.push(fit(
+                &format!(
+                    "{} {} | {} {} s | {} {}",
+                    self.lang.get_literal("RESULT_EXITCODE"),
+                    result.get_exit_code(),
+                    self.lang.get_literal("RESULT_TIME"),
+                    result.get_time(),
+                    self.lang.get_literal("RESULT_MEMORY"),
+                    memory
+                ),
+                detail_width,
+            ));
+
+            let mut tabs = String::new();
+            for section in SECTIONS {
+                let name = self.lang.get_literal(section.literal());
+                if section == self.section {
+                    tabs.push_str(&format!("{}[{}]{} ", style::Invert, name, style::Reset));
+                } else {
+                    tabs.push_str(&format!(" {}  ", name));
+                }
+            }
+            right.push(tabs);
+
+            let lines = self.lines.as_deref().unwrap_or_default();
+            let visible = height.saturating_sub(5);
+            self.scroll = self.scroll.min(lines.len().saturating_sub(visible));
+            for line in lines.iter().skip(self.scroll).take(visible) {
+                right.push(fit(line, detail_width));
+            }
+        } else {
+            right.push(self.lang.get_literal("BROWSER_NO_TESTS").to_string());
+        }
+
+        write!(screen, "{}", clear::All)?;
+        for row in 0..height.saturating_sub(2) {
+            write!(screen, "{}", cursor::Goto(1, row as u16 + 1))?;
+            match left.get(row) {
+                Some(line) if row > 0 => write!(screen, "{}", line)?,
+                Some(line) => write!(screen, "{}", fit(line, list_width))?,
+                None => write!(screen, "{}", fit("", list_width))?,
+            }
+            write!(
+                screen,
+                "{}│{}",
+                cursor::Goto(list_width as u16 + 1, row as u16 + 1),
+                right.get(row).map(String::as_str).unwrap_or("")
+            )?;
+        }
+
+        write!(
+            screen,
+            "{}{}{}",
+            cursor::Goto(1, height.saturating_sub(1) as u16),
+            fit(&self.status, width as usize),
+            cursor::Goto(1, height as u16)
+        )?;
+        write!(
+            screen,
+            "{}{}{}{}",
+            style::Invert,
+            fit(self.lang.get_literal("BROWSER_HELP"), width as usize),
+            style::Reset,
+            cursor::Hide
+        )?;
+        screen.flush()
+    }
+
+    /// Runs the selected test again and replaces its result
+    fn rerun_selected(&mut self) {
+        if let Some(index) = self.selected_result() {
+            let result = testing::rerun(&self.results[index], self.settings);
+            self.status = format!(
+                "{} {}",
+                self.lang.get_literal("BROWSER_RERUN_DONE"),
+                result.get_name()
+            );
+            self.results[index] = result;
+            self.lines = None;
+        }
+    }
+
+    /// Opens files of the selected test with $EDITOR (or less)
+    fn open_selected(&mut self) {
+        if let Some(index) = self.selected_result() {
+            let editor = env::var("EDITOR").unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+            let opened = Command::new(&editor)
+                .args(self.results[index].get_test_files())
+                .status();
+            self.status = match opened {
+                Ok(_) => String::new(),
+                Err(e) => format!(
+                    "{} {} ({})",
+                    self.lang.get_literal("BROWSER_OPEN_FAILED"),
+                    editor,
+                    e
+                ),
+            };
+        }
+    }
+}
+
+/**
+Shows results in interactive browser until user quits it. Results are updated,
+when tests are run again. Standard output has to be a terminal.
+*/
+pub fn browse<T: LiteralGenerator>(
+    results: &mut Vec<TestResult>,
+    settings: &Options,
+    lang: &T,
+) -> io::Result<()> {
+    let mut browser = Browser {
+        results,
+        settings,
+        lang,
+        filter: Filter::All,
+        section: Section::Diff,
+        selected: 0,
+        list_offset: 0,
+        scroll: 0,
+        lines: None,
+        status: String::new(),
+    };
+
+    let mut keys = io::stdin().keys();
+    loop {
+        let mut screen = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+        let mut suspend = false;
+
+        while !suspend {
+            browser.draw(&mut screen)?;
+            let key = match keys.next() {
+                Some(key) => key?,
+                None => return Ok(()),
+            };
+
+            match key {
+                Key::Char('q') | Key::Esc | Key::Ctrl('c') => {
+                    write!(screen, "{}", cursor::Show)?;
+                    return Ok(());
+                }
+                Key::Up | Key::Char('k') => browser.move_selection(-1),
+                Key::Down | Key::Char('j') => browser.move_selection(1),
+                Key::PageUp => browser.scroll = browser.scroll.saturating_sub(10),
+                Key::PageDown => browser.scroll += 10,
+                Key::Char('\t') => {
+                    browser.section = browser.section.next();
+                    browser.scroll = 0;
+                    browser.lines = None;
+                }
+                Key::Char('f') => {
+                    browser.filter = browser.filter.next();
+                    browser.selected = 0;
+                    browser.scroll = 0;
+                    browser.lines = None;
+                }
+                Key::Char('r') => {
+                    browser.status = browser.lang.get_literal("BROWSER_RERUNNING").to_string();
+                    browser.draw(&mut screen)?;
+                    browser.rerun_selected();
+                }
+                Key::Char('o') => suspend = true,
+                _ => {}
+            }
+        }
+
+        // Terminal is given back to the editor, browser is shown again after it exits.
+        write!(screen, "{}", cursor::Show)?;
+        drop(screen);
+        browser.open_selected();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_test() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 3), "abc");
+        assert_eq!(fit("a\tb", 6), "a    b");
+    }
+}
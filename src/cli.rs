@@ -0,0 +1,89 @@
+//! Command line arguments of the program. Without arguments the interactive menu is shown.
+
+/// Description of accepted arguments
+pub const USAGE: &str =
+    "Usage: r_test [--profile NAME] [--set SETTING=VALUE]... [--show-config | --doctor | --lint]
+    --profile NAME        uses a profile from config files (same as --set profile=NAME)
+    --set SETTING=VALUE   overrides a setting (e.g. --set jobs=4 --set is_valgrind_active=false)
+    --show-config         shows effective settings and where each value came from
+    --doctor              checks tested program, test folder and needed tools
+    --lint                checks files of tests (missing, orphan, duplicate, line endings...)";
+
+/// Parsed command line arguments
+#[derive(Debug, Default, PartialEq)]
+pub struct Arguments {
+    /// Settings overridden with --set, in given order
+    pub overrides: Vec<(String, String)>,
+    /// Show effective configuration instead of running the menu
+    pub show_config: bool,
+    /// Run diagnostics instead of the menu
+    pub doctor: bool,
+    /// Lint test folder instead of the menu
+    pub lint: bool,
+}
+
+impl Arguments {
+    /// Parses arguments (without name of the program), returns description of the first error
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Arguments, String> {
+        let mut arguments = Arguments::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--show-config" => arguments.show_config = true,
+                "--doctor" => arguments.doctor = true,
+                "--lint" => arguments.lint = true,
+                "--profile" => {
+                    let profile = args
+                        .next()
+                        .ok_or_else(|| "missing NAME after --profile".to_string())?;
+                    arguments.overrides.push(("profile".to_string(), profile));
+                }
+                "--set" => {
+                    let setting = args
+                        .next()
+                        .ok_or_else(|| "missing SETTING=VALUE after --set".to_string())?;
+                    arguments.overrides.push(split_setting(&setting)?);
+                }
+                _ => match arg.strip_prefix("--set=") {
+                    Some(setting) => arguments.overrides.push(split_setting(setting)?),
+                    None => return Err(format!("unknown argument '{}'", arg)),
+                },
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+fn split_setting(setting: &str) -> Result<(String, String), String> {
+    match setting.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected SETTING=VALUE, got '{}'", setting)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let args = [
+            "--set",
+            "jobs=4",
+            "--set=build_command=make -j",
+            "--show-config",
+        ];
+        let arguments = Arguments::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+
+        assert!(arguments.show_config);
+        assert_eq!(
+            arguments.overrides,
+            vec![
+                ("jobs".to_string(), "4".to_string()),
+                ("build_command".to_string(), "make -j".to_string())
+            ]
+        );
+        assert!(Arguments::parse(["--set".to_string()].into_iter()).is_err());
+        assert!(Arguments::parse(["-x".to_string()].into_iter()).is_err());
+    }
+}
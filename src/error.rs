@@ -0,0 +1,77 @@
+//! Errors of the program: input and output on files of tests and settings, running
+//! of child processes and invalid configuration. Errors of a single test are recorded
+//! in its result as an inner problem, so the rest of tests can still be conducted.
+
+use std::fmt;
+use std::io;
+
+/// Error of reading settings, loading tests or running a test
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file or directory (given path) failed
+    Io { path: String, source: io::Error },
+    /// Child process (given program) could not be started or waited for
+    Process { program: String, source: io::Error },
+    /// Child process (given program) was terminated without an exit code
+    NoExitCode(String),
+    /// Settings or profiles are not valid
+    Config(String),
+    /// Pool of threads running tests could not be created
+    ThreadPool(String),
+}
+
+/// Result with the error of the program
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Returns a closure turning an I/O error into an error about given path
+    pub fn io(path: impl Into<String>) -> impl FnOnce(io::Error) -> Error {
+        let path = path.into();
+        move |source| Error::Io { path, source }
+    }
+
+    /// Returns a closure turning an I/O error into an error about given program
+    pub fn process(program: impl Into<String>) -> impl FnOnce(io::Error) -> Error {
+        let program = program.into();
+        move |source| Error::Process { program, source }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Process { program, source } => {
+                write!(f, "Running {} FAILED: {}", program, source)
+            }
+            Error::NoExitCode(program) => write!(f, "{} was terminated without exit code", program),
+            Error::Config(message) => write!(f, "Invalid configuration: {}", message),
+            Error::ThreadPool(message) => write!(f, "Creating pool of threads FAILED: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Process { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let error = Error::io("tests/1.in")(io::Error::from(io::ErrorKind::NotFound));
+        assert!(error.to_string().starts_with("tests/1.in: "));
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(
+            Error::NoExitCode("diff".to_string()).to_string(),
+            "diff was terminated without exit code"
+        );
+    }
+}
@@ -1,9 +1,25 @@
+use crate::browser;
 use crate::language::LiteralGenerator;
-use crate::settings::Options;
-use crate::testing::{self, TestResult};
 use prettytable::{color, row, Attr, Cell, Row, Table};
+use r_test::error::{self, Error};
+use r_test::settings::config::{self, Source};
+use r_test::settings::Options;
+use r_test::testing::batch::{self, Gradebook};
+use r_test::testing::benchmark::{self, BenchmarkResult, Statistics};
+use r_test::testing::build_step::{self, BuildOutcome};
+use r_test::testing::cancel;
+use r_test::testing::doctor::{self, Diagnosis, Severity};
+use r_test::testing::lint::{self, Finding};
+use r_test::testing::manifest::Manifest;
+use r_test::testing::record;
+use r_test::testing::reduce::{self, ReduceOutcome};
+use r_test::testing::scoring;
+use r_test::testing::stress::{self, StressOutcome};
+use r_test::testing::watch::{self, WatchEvent};
+use r_test::testing::{self, Runner, TestResult};
 use std::fs::File;
 use std::io::{self, Write};
+use std::path::Path;
 
 fn clear_console() {
     print!("{}[2J", 27 as char);
@@ -59,62 +75,633 @@ fn print_options<T: LiteralGenerator>(settings: &Options, lang: &T) {
         lang.get_literal("PROGRAM_MODE"),
         settings.get_program_mode()
     );
+    println!(
+        "        {} {}",
+        lang.get_literal("WARNINGS_AS_FAILURES"),
+        settings.get_warnings_as_failures()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("WARNING_FILTER"),
+        settings.get_warning_filter().join(", ")
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("BUILD_CACHE"),
+        settings.get_build_cache_usage()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("BUILD_COMMAND"),
+        settings.get_build_command()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("SOURCES_PATH"),
+        settings.get_sources_path()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("SUBMISSIONS_PATH"),
+        settings.get_submissions_path()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("VALGRIND_CREDIT"),
+        settings.get_valgrind_credit()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("WARNINGS_CREDIT"),
+        settings.get_warnings_credit()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("PROGRAM_COMMAND"),
+        settings.get_program_command()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("GENERATOR_PATH"),
+        settings.get_generator_path()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("REFERENCE_PATH"),
+        settings.get_reference_path()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("STRESS_ITERATIONS"),
+        settings.get_stress_iterations()
+    );
+    println!("        {}", lang.get_literal("TOOLCHAINS"));
+    for (extension, command) in settings.get_toolchains() {
+        println!("            .{}: {}", extension, command.join(" "));
+    }
+    println!(
+        "        {} {}",
+        lang.get_literal("BENCHMARK_RUNS"),
+        settings.get_benchmark_runs()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("BENCHMARK_WARMUP"),
+        settings.get_benchmark_warmup()
+    );
+    println!(
+        "        {} {} kB",
+        lang.get_literal("MEMORY_LIMIT"),
+        settings.get_memory_limit()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("RETRIES"),
+        settings.get_retries()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("TIME_LIMIT_FACTOR"),
+        settings.get_time_limit_factor()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("JOBS"),
+        settings.get_jobs()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("FAIL_FAST"),
+        settings.get_fail_fast()
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("PROFILE"),
+        match settings.get_profile() {
+            "" => "-",
+            profile => profile,
+        }
+    );
+    println!(
+        "        {} {}",
+        lang.get_literal("EXECUTION_WRAPPER"),
+        match settings.get_execution_wrapper() {
+            "" => "-",
+            wrapper => wrapper,
+        }
+    );
     println!("        {}", lang.get_literal("WARNING_ABSOLUTE_PATH"));
     println!("        {}", lang.get_literal("WARNING_LANGUAGE"));
     println!("        {}", lang.get_literal("WARNING_COMPILATION"));
-    print!("        {} ", lang.get_literal("CHOOSE_OPTION_RETURN7"));
+    println!("        {}", lang.get_literal("WARNING_INTERPRETER"));
+    print!("        {} ", lang.get_literal("CHOOSE_OPTION_RETURN29"));
+    let _ = io::stdout().flush();
+}
+
+fn print_menu<T: LiteralGenerator>(lang: &T) {
+    print_title(lang);
+    /*println!("            1. Rozpocznij testy");
+    println!("            2. Wyświetl ustawienia");
+    println!("            3. Wyjdź");*/
+    println!("            1. {}", lang.get_literal("START_TESTS"));
+    println!("            2. {}", lang.get_literal("SHOW_SETTINGS"));
+    println!("            3. {}", lang.get_literal("BATCH_GRADING"));
+    println!("            4. {}", lang.get_literal("STRESS_TESTING"));
+    println!("            5. {}", lang.get_literal("RECORD_EXPECTED"));
+    println!("            6. {}", lang.get_literal("BLESS_OUTPUTS"));
+    println!("            7. {}", lang.get_literal("REDUCE_TEST"));
+    println!("            8. {}", lang.get_literal("BENCHMARK"));
+    println!("            9. {}", lang.get_literal("BROWSE_RESULTS"));
+    println!("           10. {}", lang.get_literal("WATCH_MODE"));
+    println!("           11. {}", lang.get_literal("DOCTOR"));
+    println!("           12. {}", lang.get_literal("LINT_TESTS"));
+    println!("           13. {}", lang.get_literal("EXIT_PROGRAM"));
+    print!("\n\n\t {} ", lang.get_literal("CHOOSE_OPTION_ENTER"));
+    let _ = io::stdout().flush();
+}
+
+/**
+Drive options menu
+*/
+fn manage_options<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    clear_console();
+    print_options(settings, lang);
+    let choice = read_input(29, lang);
+
+    match choice {
+        1 => {
+            manage_test_dir(settings, lang);
+            manage_options(settings, lang);
+        }
+        2 => {
+            manage_program_path(settings, lang);
+            manage_options(settings, lang);
+        }
+        3 => {
+            manage_valgrind_activity(settings, lang);
+            manage_options(settings, lang);
+        }
+        4 => {
+            manage_stderr_activity(settings, lang);
+            manage_options(settings, lang);
+        }
+        5 => {
+            manage_language(settings, lang);
+            manage_options(settings, lang);
+        }
+        6 => {
+            manage_compilation_mode(settings, lang);
+            manage_options(settings, lang);
+        }
+        7 => {
+            manage_warnings_as_failures(settings, lang);
+            manage_options(settings, lang);
+        }
+        8 => {
+            manage_warning_filter(settings, lang);
+            manage_options(settings, lang);
+        }
+        9 => {
+            manage_build_cache(settings, lang);
+            manage_options(settings, lang);
+        }
+        10 => {
+            manage_build_command(settings, lang);
+            manage_options(settings, lang);
+        }
+        11 => {
+            manage_sources_path(settings, lang);
+            manage_options(settings, lang);
+        }
+        12 => {
+            manage_submissions_path(settings, lang);
+            manage_options(settings, lang);
+        }
+        13 => {
+            manage_valgrind_credit(settings, lang);
+            manage_options(settings, lang);
+        }
+        14 => {
+            manage_warnings_credit(settings, lang);
+            manage_options(settings, lang);
+        }
+        15 => {
+            manage_program_command(settings, lang);
+            manage_options(settings, lang);
+        }
+        16 => {
+            manage_generator_path(settings, lang);
+            manage_options(settings, lang);
+        }
+        17 => {
+            manage_reference_path(settings, lang);
+            manage_options(settings, lang);
+        }
+        18 => {
+            manage_stress_iterations(settings, lang);
+            manage_options(settings, lang);
+        }
+        19 => {
+            manage_toolchain(settings, lang);
+            manage_options(settings, lang);
+        }
+        20 => {
+            manage_benchmark_runs(settings, lang);
+            manage_options(settings, lang);
+        }
+        21 => {
+            manage_benchmark_warmup(settings, lang);
+            manage_options(settings, lang);
+        }
+        22 => {
+            manage_memory_limit(settings, lang);
+            manage_options(settings, lang);
+        }
+        23 => {
+            manage_retries(settings, lang);
+            manage_options(settings, lang);
+        }
+        24 => {
+            manage_time_limit_factor(settings, lang);
+            manage_options(settings, lang);
+        }
+        25 => {
+            manage_jobs(settings, lang);
+            manage_options(settings, lang);
+        }
+        26 => {
+            manage_fail_fast(settings, lang);
+            manage_options(settings, lang);
+        }
+        27 => {
+            manage_profile(settings, lang);
+            manage_options(settings, lang);
+        }
+        28 => {
+            manage_execution_wrapper(settings, lang);
+            manage_options(settings, lang);
+        }
+        _ => {}
+    }
+}
+
+fn manage_warnings_as_failures<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("WARNINGS_USAGE"));
+        let _ = io::stdout().flush();
+
+        match read_bool_stdin() {
+            Some(option) => {
+                settings.set_warnings_as_failures(option);
+                break;
+            }
+            None => {
+                println!("{}", lang.get_literal("INCORRECT_VALUE"));
+            }
+        }
+    }
+}
+
+fn manage_build_cache<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("BUILD_CACHE_USAGE"));
+        let _ = io::stdout().flush();
+
+        match read_bool_stdin() {
+            Some(option) => {
+                settings.set_build_cache_usage(option);
+                break;
+            }
+            None => {
+                println!("{}", lang.get_literal("INCORRECT_VALUE"));
+            }
+        }
+    }
+}
+
+fn manage_fail_fast<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("FAIL_FAST_USAGE"));
+        let _ = io::stdout().flush();
+
+        match read_bool_stdin() {
+            Some(option) => {
+                settings.set_fail_fast(option);
+                break;
+            }
+            None => {
+                println!("{}", lang.get_literal("INCORRECT_VALUE"));
+            }
+        }
+    }
+}
+
+fn manage_profile<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    let profiles = config::profile_names(settings);
+    if profiles.is_empty() {
+        println!("{}", lang.get_literal("NO_PROFILES"));
+    } else {
+        println!(
+            "{} {}",
+            lang.get_literal("AVAILABLE_PROFILES"),
+            profiles.join(", ")
+        );
+    }
+
+    loop {
+        print!("{} ", lang.get_literal("GET_PROFILE"));
+        let _ = io::stdout().flush();
+        let mut profile = String::new();
+        io::stdin().read_line(&mut profile).expect("IO ERROR");
+
+        match config::switch_profile(settings, profile.trim()) {
+            Ok(()) => break,
+            Err(e) => println!("{} ({})", lang.get_literal("INCORRECT_VALUE"), e),
+        }
+    }
+}
+
+fn manage_build_command<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    print!("{} ", lang.get_literal("GET_BUILD_COMMAND"));
+    let _ = io::stdout().flush();
+    let mut command = String::new();
+
+    io::stdin().read_line(&mut command).expect("IO ERROR");
+
+    settings.set_build_command(command.trim());
+}
+
+fn manage_sources_path<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_SOURCES_PATH"));
+        let _ = io::stdout().flush();
+        let mut path = String::new();
+
+        io::stdin().read_line(&mut path).expect("IO ERROR");
+
+        if settings.set_sources_path(path.trim()) {
+            break;
+        } else {
+            println!("{}", lang.get_literal("INCORRECT_PATH"));
+        }
+    }
+}
+
+fn manage_submissions_path<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_SUBMISSIONS_PATH"));
+        let _ = io::stdout().flush();
+        let mut path = String::new();
+
+        io::stdin().read_line(&mut path).expect("IO ERROR");
+
+        if settings.set_submissions_path(path.trim()) {
+            break;
+        } else {
+            println!("{}", lang.get_literal("INCORRECT_PATH"));
+        }
+    }
+}
+
+/// Reads a floating point number from standard input
+fn read_float_stdin() -> Option<f32> {
+    let mut value = String::new();
+    io::stdin().read_line(&mut value).expect("IO ERROR");
+
+    value.trim().parse().ok()
+}
+
+fn manage_valgrind_credit<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_VALGRIND_CREDIT"));
+        let _ = io::stdout().flush();
+
+        match read_float_stdin() {
+            Some(credit) if settings.set_valgrind_credit(credit) => break,
+            _ => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+        }
+    }
+}
+
+fn manage_warnings_credit<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_WARNINGS_CREDIT"));
+        let _ = io::stdout().flush();
+
+        match read_float_stdin() {
+            Some(credit) if settings.set_warnings_credit(credit) => break,
+            _ => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+        }
+    }
+}
+
+fn manage_time_limit_factor<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_TIME_LIMIT_FACTOR"));
+        let _ = io::stdout().flush();
+
+        match read_float_stdin() {
+            Some(factor) if settings.set_time_limit_factor(factor) => break,
+            _ => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+        }
+    }
+}
+
+fn manage_generator_path<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_GENERATOR_PATH"));
+        let _ = io::stdout().flush();
+        let mut path = String::new();
+
+        io::stdin().read_line(&mut path).expect("IO ERROR");
+
+        if settings.set_generator_path(path.trim()) {
+            break;
+        } else {
+            println!("{}", lang.get_literal("INCORRECT_PATH"));
+        }
+    }
+}
+
+fn manage_reference_path<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_REFERENCE_PATH"));
+        let _ = io::stdout().flush();
+        let mut path = String::new();
+
+        io::stdin().read_line(&mut path).expect("IO ERROR");
+
+        if settings.set_reference_path(path.trim()) {
+            break;
+        } else {
+            println!("{}", lang.get_literal("INCORRECT_PATH"));
+        }
+    }
+}
+
+fn manage_stress_iterations<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_STRESS_ITERATIONS"));
+        let _ = io::stdout().flush();
+        let mut iterations = String::new();
+
+        io::stdin().read_line(&mut iterations).expect("IO ERROR");
+
+        match iterations.trim().parse() {
+            Ok(iterations) => {
+                settings.set_stress_iterations(iterations);
+                break;
+            }
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_benchmark_runs<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_BENCHMARK_RUNS"));
+        let _ = io::stdout().flush();
+        let mut runs = String::new();
+
+        io::stdin().read_line(&mut runs).expect("IO ERROR");
+
+        match runs.trim().parse() {
+            Ok(runs) if settings.set_benchmark_runs(runs) => break,
+            Ok(_) => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_benchmark_warmup<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_BENCHMARK_WARMUP"));
+        let _ = io::stdout().flush();
+        let mut runs = String::new();
+
+        io::stdin().read_line(&mut runs).expect("IO ERROR");
+
+        match runs.trim().parse() {
+            Ok(runs) => {
+                settings.set_benchmark_warmup(runs);
+                break;
+            }
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_memory_limit<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_MEMORY_LIMIT"));
+        let _ = io::stdout().flush();
+        let mut limit = String::new();
+
+        io::stdin().read_line(&mut limit).expect("IO ERROR");
+
+        match limit.trim().parse() {
+            Ok(limit) => {
+                settings.set_memory_limit(limit);
+                break;
+            }
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_retries<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_RETRIES"));
+        let _ = io::stdout().flush();
+        let mut retries = String::new();
+
+        io::stdin().read_line(&mut retries).expect("IO ERROR");
+
+        match retries.trim().parse() {
+            Ok(retries) => {
+                settings.set_retries(retries);
+                break;
+            }
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_jobs<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_JOBS"));
+        let _ = io::stdout().flush();
+        let mut jobs = String::new();
+
+        io::stdin().read_line(&mut jobs).expect("IO ERROR");
+
+        match jobs.trim().parse() {
+            Ok(jobs) => {
+                settings.set_jobs(jobs);
+                break;
+            }
+            Err(_) => println!("{}", lang.get_literal("READ_ERROR_NUMBER")),
+        }
+    }
+}
+
+fn manage_program_command<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    print!("{} ", lang.get_literal("GET_PROGRAM_COMMAND"));
     let _ = io::stdout().flush();
+    let mut command = String::new();
+
+    io::stdin().read_line(&mut command).expect("IO ERROR");
+
+    settings.set_program_command(command.trim());
 }
 
-fn print_menu<T: LiteralGenerator>(lang: &T) {
-    print_title(lang);
-    /*println!("            1. Rozpocznij testy");
-    println!("            2. Wyświetl ustawienia");
-    println!("            3. Wyjdź");*/
-    println!("            1. {}", lang.get_literal("START_TESTS"));
-    println!("            2. {}", lang.get_literal("SHOW_SETTINGS"));
-    println!("            3. {}", lang.get_literal("EXIT_PROGRAM"));
-    print!("\n\n\t {} ", lang.get_literal("CHOOSE_OPTION_ENTER"));
+fn manage_execution_wrapper<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    print!("{} ", lang.get_literal("GET_EXECUTION_WRAPPER"));
     let _ = io::stdout().flush();
+    let mut wrapper = String::new();
+
+    io::stdin().read_line(&mut wrapper).expect("IO ERROR");
+
+    settings.set_execution_wrapper(wrapper.trim());
 }
 
-/**
-Drive options menu
-*/
-fn manage_options<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
-    clear_console();
-    print_options(settings, lang);
-    let choice = read_input(7, lang);
+fn manage_toolchain<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    loop {
+        print!("{} ", lang.get_literal("GET_TOOLCHAIN"));
+        let _ = io::stdout().flush();
+        let mut toolchain = String::new();
 
-    match choice {
-        1 => {
-            manage_test_dir(settings, lang);
-            manage_options(settings, lang);
-        }
-        2 => {
-            manage_program_path(settings, lang);
-            manage_options(settings, lang);
-        }
-        3 => {
-            manage_valgrind_activity(settings, lang);
-            manage_options(settings, lang);
-        }
-        4 => {
-            manage_stderr_activity(settings, lang);
-            manage_options(settings, lang);
-        }
-        5 => {
-            manage_language(settings, lang);
-            manage_options(settings, lang);
-        }
-        6 => {
-            manage_compilation_mode(settings, lang);
-            manage_options(settings, lang);
+        io::stdin().read_line(&mut toolchain).expect("IO ERROR");
+
+        match toolchain.trim().split_once('=') {
+            Some((extension, command)) if !extension.trim().is_empty() => {
+                let extension = extension.trim().trim_start_matches('.');
+                let command = command.split_whitespace().map(str::to_string).collect();
+                settings.set_toolchain(extension, command);
+                break;
+            }
+            _ => {
+                println!("{}", lang.get_literal("INCORRECT_VALUE"));
+            }
         }
-        _ => {}
     }
 }
 
+fn manage_warning_filter<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
+    print!("{} ", lang.get_literal("GET_WARNING_FILTER"));
+    let _ = io::stdout().flush();
+    let mut filter = String::new();
+
+    io::stdin().read_line(&mut filter).expect("IO ERROR");
+
+    let flags = filter
+        .split(',')
+        .map(|flag| flag.trim().to_string())
+        .filter(|flag| !flag.is_empty())
+        .collect();
+    settings.set_warning_filter(flags);
+}
+
 fn manage_compilation_mode<T: LiteralGenerator>(settings: &mut Options, lang: &T) {
     loop {
         print!("{} ", lang.get_literal("COMPILATION_USAGE"));
@@ -263,14 +850,86 @@ fn read_input<T: LiteralGenerator>(maximum_number: u8, lang: &T) -> u8 {
 pub fn start_program<T: LiteralGenerator>(settings: &mut Options, dict: &T) {
     clear_console();
     print_menu(dict);
-    let choice = read_input(3, dict);
+    let choice = read_input(13, dict);
 
     match choice {
         1 => {
-            let results = testing::run_testing(settings);
-            print_results(&results, dict);
+            if prepare_program(settings, dict) {
+                match testing::run_testing(settings) {
+                    Ok(results) => print_results(&results, settings, dict),
+                    Err(e) => eprintln!("{} ({})", dict.get_literal("LOAD_TESTS_FAILED"), e),
+                }
+            }
         }
         3 => {
+            clear_console();
+            match batch::run_batch(settings) {
+                Ok(gradebook) => print_gradebook(&gradebook, dict),
+                Err(e) => eprintln!("{} ({})", dict.get_literal("BATCH_FAILED"), e),
+            }
+        }
+        4 => {
+            clear_console();
+            print_stress_outcome(&stress::run_stress(settings), dict);
+        }
+        5 => {
+            clear_console();
+            let (recorded, failed) = record::record_expected(settings);
+            println!("{} {}", dict.get_literal("RECORDED_TESTS"), recorded.len());
+            for description in failed {
+                eprintln!("{} {}", dict.get_literal("RECORD_FAILED"), description);
+            }
+        }
+        6 => {
+            if prepare_program(settings, dict) {
+                bless_failures(settings, dict);
+            }
+        }
+        7 => {
+            let test_path = read_reduce_test(settings, dict);
+            if prepare_program(settings, dict) {
+                print_reduce_outcome(&reduce::reduce_test(settings, &test_path), dict);
+            }
+        }
+        8 => {
+            if prepare_program(settings, dict) {
+                match benchmark::run_benchmark(settings) {
+                    Ok(results) => print_benchmark(&results, dict),
+                    Err(e) => eprintln!("{} ({})", dict.get_literal("LOAD_TESTS_FAILED"), e),
+                }
+            }
+        }
+        9 => {
+            if prepare_program(settings, dict) {
+                let mut results = match testing::run_testing(settings) {
+                    Ok(results) => results,
+                    Err(e) => {
+                        eprintln!("{} ({})", dict.get_literal("LOAD_TESTS_FAILED"), e);
+                        return;
+                    }
+                };
+                if termion::is_tty(&io::stdout()) {
+                    if let Err(e) = browser::browse(&mut results, settings, dict) {
+                        eprintln!("{} ({})", dict.get_literal("BROWSER_FAILED"), e);
+                    }
+                }
+                print_results(&results, settings, dict);
+            }
+        }
+        10 => {
+            if prepare_program(settings, dict) {
+                watch_tests(settings, dict);
+            }
+        }
+        11 => {
+            clear_console();
+            print_diagnoses(&doctor::diagnose(settings), dict);
+        }
+        12 => {
+            clear_console();
+            lint_tests(settings, dict);
+        }
+        13 => {
             clear_console();
             println!("{}", dict.get_literal("PROGRAM_END"));
         }
@@ -281,6 +940,134 @@ pub fn start_program<T: LiteralGenerator>(settings: &mut Options, dict: &T) {
     }
 }
 
+/**
+Runs build step of tested program and warns about stale executable.
+Returns false when testing should be aborted.
+*/
+fn prepare_program<T: LiteralGenerator>(settings: &Options, lang: &T) -> bool {
+    clear_console();
+
+    match build_step::run_build_command(settings) {
+        BuildOutcome::Skipped => {}
+        BuildOutcome::Built => println!("{}", lang.get_literal("BUILD_SUCCEEDED")),
+        BuildOutcome::Failed(cause) => {
+            eprintln!("{} ({})", lang.get_literal("BUILD_FAILED"), cause);
+            return false;
+        }
+    }
+
+    let problems = doctor::validate(settings);
+    if !problems.is_empty() {
+        print_diagnoses(&problems, lang);
+        if problems
+            .iter()
+            .any(|problem| problem.severity == Severity::Error)
+        {
+            eprintln!("{}", lang.get_literal("VALIDATION_FAILED"));
+            return false;
+        }
+        std::thread::sleep(std::time::Duration::from_secs(3));
+    }
+
+    let stale = build_step::newer_sources(settings);
+    if !stale.is_empty() {
+        println!("{}", lang.get_literal("STALE_EXECUTABLE"));
+        for source in stale {
+            println!("    {}", source);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(3));
+    }
+    true
+}
+
+/// Prints findings of diagnostics with their severity
+pub fn print_diagnoses<T: LiteralGenerator>(diagnoses: &[Diagnosis], lang: &T) {
+    let mut table = Table::new();
+    for diagnosis in diagnoses {
+        let severity = match diagnosis.severity {
+            Severity::Ok => Cell::new(lang.get_literal("DOCTOR_OK"))
+                .with_style(Attr::ForegroundColor(color::GREEN)),
+            Severity::Warning => Cell::new(lang.get_literal("DOCTOR_WARNING"))
+                .with_style(Attr::ForegroundColor(color::YELLOW)),
+            Severity::Error => Cell::new(lang.get_literal("DOCTOR_ERROR"))
+                .with_style(Attr::ForegroundColor(color::RED)),
+        };
+        table.add_row(Row::new(vec![
+            severity,
+            Cell::new(lang.get_literal(diagnosis.message)),
+            Cell::new(&truncate(&diagnosis.detail)),
+        ]));
+    }
+    table.printstd();
+}
+
+/// Prints findings of test suite linting
+pub fn print_findings<T: LiteralGenerator>(findings: &[Finding], lang: &T) {
+    if findings.is_empty() {
+        println!("{}", lang.get_literal("LINT_CLEAN"));
+        return;
+    }
+
+    let mut table = Table::new();
+    table.add_row(row![
+        lang.get_literal("LINT_FILE"),
+        lang.get_literal("LINT_PROBLEM"),
+        lang.get_literal("LINT_FIXABLE")
+    ]);
+    for finding in findings {
+        let mut problem = lang.get_literal(finding.issue.literal()).to_string();
+        if let lint::Issue::DuplicateInput(first) = &finding.issue {
+            problem.push(' ');
+            problem.push_str(first);
+        }
+        let fixable = if finding.fixable() {
+            Cell::new(lang.get_literal("RESULT_TRUE_OUT"))
+                .with_style(Attr::ForegroundColor(color::GREEN))
+        } else {
+            Cell::new("-")
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&finding.get_name()).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
+            Cell::new(&problem),
+            fixable,
+        ]));
+    }
+    table.printstd();
+}
+
+/// Lints test folder and offers to fix safe issues
+fn lint_tests<T: LiteralGenerator>(settings: &Options, lang: &T) {
+    let findings = match lint::lint(settings) {
+        Ok(findings) => findings,
+        Err(e) => {
+            eprintln!("{} ({})", lang.get_literal("LINT_FAILED"), e);
+            return;
+        }
+    };
+    print_findings(&findings, lang);
+
+    let fixable = findings.iter().filter(|finding| finding.fixable()).count();
+    if fixable == 0 {
+        return;
+    }
+    loop {
+        print!("{} ", lang.get_literal("LINT_FIX_QUESTION"));
+        let _ = io::stdout().flush();
+
+        match read_bool_stdin() {
+            Some(true) => {
+                match lint::fix(&findings) {
+                    Ok(fixed) => println!("{} {}", lang.get_literal("LINT_FIXED"), fixed),
+                    Err(e) => eprintln!("{} ({})", lang.get_literal("LINT_FAILED"), e),
+                }
+                break;
+            }
+            Some(false) => break,
+            None => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+        }
+    }
+}
+
 /**
 Add a endline char every 200 characters without newline.
 */
@@ -316,11 +1103,18 @@ fn print_table<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
     let time = lang.get_literal("RESULT_TIME");
     let passed = lang.get_literal("RESULT_PASSED");
     let overall = lang.get_literal("RESULT_OUTCOME");
+    let warnings = lang.get_literal("RESULT_WARNINGS");
+    let memory = lang.get_literal("RESULT_MEMORY");
+    let cpu = lang.get_literal("RESULT_CPU");
+    let flip_rate = lang.get_literal("RESULT_FLIP_RATE");
 
     let test_true = lang.get_literal("RESULT_TRUE_OUT");
     let test_false = lang.get_literal("RESULT_FALSE_OUT");
+    let test_flaky = lang.get_literal("RESULT_FLAKY_OUT");
 
-    show_result.add_row(row![id, name, time, passed, overall]);
+    show_result.add_row(row![
+        id, name, time, memory, cpu, passed, flip_rate, overall, warnings
+    ]);
     for (index, result) in results.iter().enumerate() {
         let id = (index + 1).to_string();
         let name = result.get_name();
@@ -329,16 +1123,40 @@ fn print_table<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
             lang.get_literal("RESULT_EXITCODE"),
             result.get_exit_code()
         );
+        let warnings = match result.get_compilation_warnings() {
+            Some(warnings) => truncate(warnings),
+            None => "-".to_string(),
+        };
+        let (memory, cpu) = match result.get_usage() {
+            Some(usage) => (
+                format!("{} kB", usage.max_rss_kb),
+                format!("{:.3} / {:.3} s", usage.user, usage.system),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        let flip_rate = match result.get_flip_rate() {
+            Some(rate) => format!("{:.0}%", rate * 100.0),
+            None => "-".to_string(),
+        };
 
         if result.passed() {
             let mut time = result.get_time().to_string();
             time.push_str(" s");
+            let verdict = if result.flaky() {
+                Cell::new(test_flaky).with_style(Attr::ForegroundColor(color::YELLOW))
+            } else {
+                Cell::new(test_true).with_style(Attr::ForegroundColor(color::GREEN))
+            };
             show_result.add_row(Row::new(vec![
                 Cell::new(&id),
                 Cell::new(&name).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
                 Cell::new(&time).with_style(Attr::ForegroundColor(color::YELLOW)),
-                Cell::new(test_true).with_style(Attr::ForegroundColor(color::GREEN)),
+                Cell::new(&memory),
+                Cell::new(&cpu),
+                verdict,
+                Cell::new(&flip_rate),
                 Cell::new(&code),
+                Cell::new(&warnings).with_style(Attr::ForegroundColor(color::YELLOW)),
             ]));
         } else {
             let mut description = result.get_problem_description();
@@ -349,33 +1167,62 @@ fn print_table<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
                 Cell::new(&id),
                 Cell::new(&name).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
                 Cell::new("-"),
+                Cell::new(&memory),
+                Cell::new(&cpu),
                 Cell::new(test_false).with_style(Attr::ForegroundColor(color::RED)),
+                Cell::new(&flip_rate),
                 Cell::new(&description),
+                Cell::new(&warnings).with_style(Attr::ForegroundColor(color::YELLOW)),
             ]));
         }
     }
     show_result.printstd();
-    let content = show_result.to_string();
-    let mut output = File::create("history.log").unwrap();
-    let _ = write!(output, "{}", content);
+    if let Err(e) = save_history_log(&show_result.to_string()) {
+        eprintln!("ERROR: Saving history.log FAILED. ({})", e);
+    }
+}
+
+/// Saves table with results of tests to a history.log file in main directory
+fn save_history_log(content: &str) -> error::Result<()> {
+    let mut output = File::create("history.log").map_err(Error::io("history.log"))?;
+    write!(output, "{}", content).map_err(Error::io("history.log"))
 }
 
 /**
 Prints out summary of conducted tests
 */
-fn print_summary<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
+fn print_summary<T: LiteralGenerator>(results: &[TestResult], settings: &Options, lang: &T) {
     let mut passed = 0;
     let mut valgrind_failed = 0;
     let mut diff_failed = 0;
     let mut other_failed = 0;
+    let mut warnings_failed = 0;
+    let mut memory_failed = 0;
+    let mut time_failed = 0;
+    let mut with_warnings = 0;
+    let mut flaky = 0;
 
     for result in results {
+        if result.get_compilation_warnings().is_some() {
+            with_warnings += 1;
+        }
+
+        if result.flaky() {
+            flaky += 1;
+        }
+
         if result.passed() {
             passed += 1;
         } else if result.diff_error() {
             diff_failed += 1;
         } else if result.valgrind_error() {
             valgrind_failed += 1;
+        } else if result.warnings_error() {
+            warnings_failed += 1;
+        } else if result.memory_limit_error() {
+            memory_failed += 1;
+        } else if result.time_limit_error() {
+            time_failed += 1;
         } else {
             other_failed += 1;
         }
@@ -385,7 +1232,7 @@ fn print_summary<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
     summary.add_row(Row::new(vec![
         Cell::new(lang.get_literal("TEST_TOTAL"))
             .with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
-        Cell::new(&(passed + valgrind_failed + other_failed + diff_failed).to_string()),
+        Cell::new(&results.len().to_string()),
     ]));
 
     summary.add_row(Row::new(vec![
@@ -393,9 +1240,14 @@ fn print_summary<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
         Cell::new(&passed.to_string()),
     ]));
 
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_FLAKY")).with_style(Attr::ForegroundColor(color::YELLOW)),
+        Cell::new(&flaky.to_string()),
+    ]));
+
     summary.add_row(Row::new(vec![
         Cell::new(lang.get_literal("TEST_FAILED")).with_style(Attr::ForegroundColor(color::RED)),
-        Cell::new(&(valgrind_failed + other_failed + diff_failed).to_string()),
+        Cell::new(&(results.len() - passed).to_string()),
     ]));
 
     summary.add_row(Row::new(vec![
@@ -416,15 +1268,399 @@ fn print_summary<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
         Cell::new(&other_failed.to_string()),
     ]));
 
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_WARNINGS_FAILED"))
+            .with_style(Attr::ForegroundColor(color::RED)),
+        Cell::new(&warnings_failed.to_string()),
+    ]));
+
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_MEMORY_FAILED"))
+            .with_style(Attr::ForegroundColor(color::RED)),
+        Cell::new(&memory_failed.to_string()),
+    ]));
+
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_TIME_FAILED"))
+            .with_style(Attr::ForegroundColor(color::RED)),
+        Cell::new(&time_failed.to_string()),
+    ]));
+
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_WITH_WARNINGS"))
+            .with_style(Attr::ForegroundColor(color::YELLOW)),
+        Cell::new(&with_warnings.to_string()),
+    ]));
+
+    let manifest = Manifest::load(settings.get_test_path());
+    let tests: Vec<String> = match Runner::new(settings).discover() {
+        Ok(tests) => tests.iter().map(|test| test.get_name()).collect(),
+        Err(_) => results.iter().map(|result| result.get_name()).collect(),
+    };
+    let score = scoring::suite_score(results, &tests, &manifest, settings);
+    summary.add_row(Row::new(vec![
+        Cell::new(lang.get_literal("TEST_SCORE"))
+            .with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
+        Cell::new(&format!("{:.2} / {:.2}", score.earned, score.maximum)),
+    ]));
+
     summary.printstd();
 }
 
+/**
+Prints gradebook of batch grading (submissions versus tests) and saves it
+to gradebook.csv and gradebook.json files in main directory.
+*/
+fn print_gradebook<T: LiteralGenerator>(gradebook: &Gradebook, lang: &T) {
+    let mut tests = Table::new();
+    tests.add_row(row![
+        lang.get_literal("RESULT_ID"),
+        lang.get_literal("RESULT_NAME")
+    ]);
+    for (index, test) in gradebook.get_tests().iter().enumerate() {
+        tests.add_row(Row::new(vec![
+            Cell::new(&(index + 1).to_string()),
+            Cell::new(test).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
+        ]));
+    }
+    tests.printstd();
+    println!();
+
+    let mut header = vec![Cell::new(lang.get_literal("GRADEBOOK_SUBMISSION"))];
+    header.extend((1..=gradebook.get_tests().len()).map(|id| Cell::new(&id.to_string())));
+    header.push(Cell::new(lang.get_literal("TEST_PASSED")));
+    header.push(Cell::new(lang.get_literal("TEST_SCORE")));
+
+    let mut grades = Table::new();
+    grades.add_row(Row::new(header));
+    for submission in gradebook.get_submissions() {
+        let mut cells =
+            vec![Cell::new(submission.get_name())
+                .with_style(Attr::ForegroundColor(color::BRIGHT_CYAN))];
+        cells.extend(submission.get_results().iter().map(|result| {
+            if result.passed() {
+                Cell::new("+").with_style(Attr::ForegroundColor(color::GREEN))
+            } else {
+                Cell::new("-").with_style(Attr::ForegroundColor(color::RED))
+            }
+        }));
+        cells.push(Cell::new(&format!(
+            "{} / {}",
+            submission.passed_count(),
+            submission.get_results().len()
+        )));
+        let score = submission.get_score();
+        cells.push(Cell::new(&format!(
+            "{:.2} / {:.2}",
+            score.earned, score.maximum
+        )));
+        grades.add_row(Row::new(cells));
+    }
+    grades.printstd();
+
+    match gradebook.save() {
+        Ok(()) => println!("{}", lang.get_literal("GRADEBOOK_SAVED")),
+        Err(e) => eprintln!("{} ({})", lang.get_literal("GRADEBOOK_SAVE_FAILED"), e),
+    }
+}
+
+/**
+Runs tests and, for every test with wrong output, shows the difference and asks
+wheter actual output of the program should become the expected one.
+*/
+fn bless_failures<T: LiteralGenerator>(settings: &Options, lang: &T) {
+    let results = match testing::run_testing(settings) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{} ({})", lang.get_literal("LOAD_TESTS_FAILED"), e);
+            return;
+        }
+    };
+
+    for result in results.iter().filter(|result| result.diff_error()) {
+        println!();
+        println!("{} {}", lang.get_literal("RESULT_NAME"), result.get_name());
+        println!("{}", result.get_problem_description());
+        println!("{}", result.get_problem());
+
+        loop {
+            print!("{} ", lang.get_literal("BLESS_CONFIRM"));
+            let _ = io::stdout().flush();
+
+            match read_bool_stdin() {
+                Some(true) => {
+                    if let Err(e) = record::bless(result, settings) {
+                        eprintln!(
+                            "{} {}: {}",
+                            lang.get_literal("RECORD_FAILED"),
+                            result.get_name(),
+                            e
+                        );
+                    }
+                    break;
+                }
+                Some(false) => break,
+                None => println!("{}", lang.get_literal("INCORRECT_VALUE")),
+            }
+        }
+    }
+}
+
+/**
+Prints outcome of stress testing
+*/
+fn print_stress_outcome<T: LiteralGenerator>(outcome: &StressOutcome, lang: &T) {
+    match outcome {
+        StressOutcome::Passed(iterations) => {
+            println!("{} {}", lang.get_literal("STRESS_PASSED"), iterations);
+        }
+        StressOutcome::Mismatch {
+            seed,
+            saved_as,
+            diff,
+        } => {
+            println!("{} {}", lang.get_literal("STRESS_MISMATCH"), seed);
+            println!("{} {}", lang.get_literal("STRESS_SAVED"), saved_as);
+            println!("{}", diff);
+        }
+        StressOutcome::Failed(cause) => {
+            eprintln!("{} ({})", lang.get_literal("STRESS_FAILED"), cause);
+        }
+    }
+}
+
+/**
+Reads path of a test to reduce, name of a file in test folder is enough.
+*/
+fn read_reduce_test<T: LiteralGenerator>(settings: &Options, lang: &T) -> String {
+    loop {
+        print!("{} ", lang.get_literal("GET_REDUCE_TEST"));
+        let _ = io::stdout().flush();
+        let mut path = String::new();
+
+        io::stdin().read_line(&mut path).expect("IO ERROR");
+
+        let path = Path::new(path.trim());
+        let in_test_folder = Path::new(settings.get_test_path()).join(path);
+        if path.is_file() {
+            return path.to_string_lossy().to_string();
+        } else if in_test_folder.is_file() {
+            return in_test_folder.to_string_lossy().to_string();
+        } else {
+            println!("{}", lang.get_literal("INCORRECT_PATH"));
+        }
+    }
+}
+
+/**
+Prints outcome of test input reduction
+*/
+fn print_reduce_outcome<T: LiteralGenerator>(outcome: &ReduceOutcome, lang: &T) {
+    match outcome {
+        ReduceOutcome::Reduced {
+            saved_as,
+            original,
+            reduced,
+        } => {
+            println!(
+                "{} {} -> {} B",
+                lang.get_literal("REDUCE_SIZE"),
+                original,
+                reduced
+            );
+            println!("{} {}", lang.get_literal("REDUCE_SAVED"), saved_as);
+        }
+        ReduceOutcome::NotFailing => println!("{}", lang.get_literal("REDUCE_NOT_FAILING")),
+        ReduceOutcome::Failed(cause) => {
+            eprintln!("{} ({})", lang.get_literal("REDUCE_FAILED"), cause);
+        }
+    }
+}
+
+/// Formats statistics of times as min / median / mean ± stddev, in milliseconds
+fn format_statistics(statistics: &Statistics) -> String {
+    format!(
+        "{:.3} / {:.3} / {:.3} ± {:.3}",
+        statistics.min * 1000.0,
+        statistics.median * 1000.0,
+        statistics.mean * 1000.0,
+        statistics.stddev * 1000.0
+    )
+}
+
+/**
+Prints table of benchmarked tests. Wall time is shown with its statistics
+and number of outliers, user and system CPU times separately.
+*/
+fn print_benchmark<T: LiteralGenerator>(results: &[BenchmarkResult], lang: &T) {
+    let mut table = Table::new();
+    table.add_row(row![
+        lang.get_literal("RESULT_ID"),
+        lang.get_literal("RESULT_NAME"),
+        lang.get_literal("BENCHMARK_WALL"),
+        lang.get_literal("BENCHMARK_OUTLIERS"),
+        lang.get_literal("BENCHMARK_USER"),
+        lang.get_literal("BENCHMARK_SYSTEM")
+    ]);
+
+    for (index, result) in results.iter().enumerate() {
+        let id = (index + 1).to_string();
+        let name =
+            Cell::new(result.get_name()).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN));
+
+        match result.get_outcome() {
+            Ok(timings) => table.add_row(Row::new(vec![
+                Cell::new(&id),
+                name,
+                Cell::new(&format_statistics(&timings.wall))
+                    .with_style(Attr::ForegroundColor(color::YELLOW)),
+                Cell::new(&timings.wall.outliers.to_string()),
+                Cell::new(&format_statistics(&timings.user)),
+                Cell::new(&format_statistics(&timings.system)),
+            ])),
+            Err(problem) => table.add_row(Row::new(vec![
+                Cell::new(&id),
+                name,
+                Cell::new(&truncate(problem)).with_style(Attr::ForegroundColor(color::RED)),
+                Cell::new("-"),
+                Cell::new("-"),
+                Cell::new("-"),
+            ])),
+        };
+    }
+
+    clear_console();
+    println!("{}", lang.get_literal("BENCHMARK_UNITS"));
+    table.printstd();
+}
+
+/**
+Prints every setting with its effective value and layer of configuration which gave it.
+*/
+pub fn print_config_sources<T: LiteralGenerator>(settings: &Options, lang: &T) {
+    let mut table = Table::new();
+    table.add_row(row![
+        lang.get_literal("CONFIG_SETTING"),
+        lang.get_literal("CONFIG_VALUE"),
+        lang.get_literal("CONFIG_SOURCE")
+    ]);
+
+    let settings = match config::describe(settings) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("ERROR: Describing settings FAILED. ({})", e);
+            return;
+        }
+    };
+    for (key, value, source) in settings {
+        let source = match &source {
+            Source::Default => lang.get_literal("CONFIG_SOURCE_DEFAULT").to_string(),
+            Source::UserFile(path) => {
+                format!(
+                    "{} {}",
+                    lang.get_literal("CONFIG_SOURCE_USER"),
+                    path.display()
+                )
+            }
+            Source::LegacyFile(path) => {
+                format!(
+                    "{} {}",
+                    lang.get_literal("CONFIG_SOURCE_LEGACY"),
+                    path.display()
+                )
+            }
+            Source::ProjectFile(path) => {
+                format!(
+                    "{} {}",
+                    lang.get_literal("CONFIG_SOURCE_PROJECT"),
+                    path.display()
+                )
+            }
+            Source::Profile(name) => {
+                format!("{} {}", lang.get_literal("CONFIG_SOURCE_PROFILE"), name)
+            }
+            Source::Environment(variable) => {
+                format!("{} {}", lang.get_literal("CONFIG_SOURCE_ENV"), variable)
+            }
+            Source::CommandLine => lang.get_literal("CONFIG_SOURCE_CLI").to_string(),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&key).with_style(Attr::ForegroundColor(color::BRIGHT_CYAN)),
+            Cell::new(&truncate(&value)),
+            Cell::new(&source),
+        ]));
+    }
+    table.printstd();
+}
+
+/**
+Runs watch mode, showing a compact summary of every run.
+*/
+fn watch_tests<T: LiteralGenerator>(settings: &Options, lang: &T) {
+    let mut run_number = 0;
+    watch::watch(settings, |event| match event {
+        WatchEvent::Started => {
+            run_number += 1;
+            clear_console();
+            println!("{} #{}", lang.get_literal("WATCH_RUNNING"), run_number);
+        }
+        WatchEvent::BuildFailed(cause) => {
+            eprintln!("{} ({})", lang.get_literal("BUILD_FAILED"), cause);
+            println!("\n{}", lang.get_literal("WATCH_WAITING"));
+        }
+        WatchEvent::LoadFailed(cause) => {
+            eprintln!("{} ({})", lang.get_literal("LOAD_TESTS_FAILED"), cause);
+            println!("\n{}", lang.get_literal("WATCH_WAITING"));
+        }
+        WatchEvent::Finished(results) => {
+            clear_console();
+            print_compact_summary(results, lang);
+            println!("\n{}", lang.get_literal("WATCH_WAITING"));
+        }
+    });
+}
+
+/// Prints a single line summary of conducted tests and names of failed ones
+fn print_compact_summary<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
+    let passed = results.iter().filter(|result| result.passed()).count();
+    let failed = results.len() - passed;
+    let flaky = results.iter().filter(|result| result.flaky()).count();
+
+    println!(
+        "{}: {} | {}: {} | {}: {} | {}: {}",
+        lang.get_literal("TEST_TOTAL"),
+        results.len(),
+        lang.get_literal("TEST_PASSED"),
+        passed,
+        lang.get_literal("TEST_FAILED"),
+        failed,
+        lang.get_literal("TEST_FLAKY"),
+        flaky
+    );
+    for result in results.iter().filter(|result| !result.passed()) {
+        println!(
+            "    {} - {}",
+            result.get_name(),
+            result.get_problem_description()
+        );
+    }
+    if cancel::is_interrupted() {
+        println!("{}", lang.get_literal("TESTING_INTERRUPTED"));
+    }
+}
+
 /**
 Prints summary and table of conducted tests.
 */
-fn print_results<T: LiteralGenerator>(results: &[TestResult], lang: &T) {
+fn print_results<T: LiteralGenerator>(results: &[TestResult], settings: &Options, lang: &T) {
     clear_console();
     print_table(results, lang);
     println!();
-    print_summary(results, lang);
+    print_summary(results, settings, lang);
+
+    if cancel::is_interrupted() {
+        println!("{}", lang.get_literal("TESTING_INTERRUPTED"));
+    } else if cancel::is_stopped() {
+        println!("{}", lang.get_literal("TESTING_STOPPED"));
+    }
 }
@@ -0,0 +1,26 @@
+//! Library of r_test: discovery of tests, running tested programs (with valgrind or compiled
+//! test drivers) and comparison of their outputs with expected .out, .err and .code files.
+//! The r_test binary is a menu built on top of it.
+//!
+//! ```no_run
+//! use r_test::{Options, Runner};
+//!
+//! let mut settings = Options::default();
+//! settings.set_test_path("/home/user/tests");
+//! settings.set_program_path("/home/user/program");
+//!
+//! let results = Runner::new(&settings).history(false).run()?;
+//! for result in results.iter().filter(|result| !result.passed()) {
+//!     println!("{}: {}", result.get_name(), result.get_problem_description());
+//! }
+//! # Ok::<(), r_test::Error>(())
+//! ```
+
+pub mod error;
+pub mod settings;
+pub mod testing;
+
+pub use error::{Error, Result};
+pub use settings::Options;
+pub use testing::test_enums::{DiffResult, TestFail};
+pub use testing::{Runner, TestResult};
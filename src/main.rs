@@ -2,18 +2,57 @@
 //! It needs a compiled version of program to test and absolute path to it and folder with tests.
 //! Testfolder should be a directory with .in, .out and .err files with matching names.
 
-#[macro_use]
-extern crate prettytable;
+mod browser;
+mod cli;
 mod interface;
 mod language;
-mod settings;
-mod testing;
 
 use language::language_dictionary::LangDict;
+use language::LiteralGenerator;
+use r_test::{settings, testing};
 
 fn main() {
-    let mut configuration = settings::Options::new();
+    let arguments = match cli::Arguments::parse(std::env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!("ERROR: {}\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    testing::cancel::install_handler();
+    let mut configuration = match settings::Options::new(&arguments.overrides) {
+        Ok(configuration) => configuration,
+        Err(e) => {
+            eprintln!("ERROR: Loading settings FAILED. ({})", e);
+            std::process::exit(2);
+        }
+    };
     let language = LangDict::new(configuration.get_language());
+    if arguments.show_config {
+        interface::print_config_sources(&configuration, &language);
+        return;
+    }
+    if arguments.doctor {
+        let diagnoses = testing::doctor::diagnose(&configuration);
+        interface::print_diagnoses(&diagnoses, &language);
+        let failed = diagnoses
+            .iter()
+            .any(|diagnosis| diagnosis.severity == testing::doctor::Severity::Error);
+        std::process::exit(if failed { 1 } else { 0 });
+    }
+    if arguments.lint {
+        match testing::lint::lint(&configuration) {
+            Ok(findings) => {
+                interface::print_findings(&findings, &language);
+                std::process::exit(if findings.is_empty() { 0 } else { 1 });
+            }
+            Err(e) => {
+                eprintln!("{} ({})", language.get_literal("LINT_FAILED"), e);
+                std::process::exit(2);
+            }
+        }
+    }
     interface::start_program(&mut configuration, &language);
     configuration.save();
 }
@@ -1,7 +1,13 @@
-use std::fs;
+pub mod config;
+
+use std::collections::BTreeMap;
 use std::path;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+/// Flags passed to gcc and g++ by default toolchains
+const GNU_FLAGS: [&str; 4] = ["-O2", "-Wall", "-Wextra", "-Wno-implicit-fallthrough"];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 /// Structure to manage program configuration
 pub struct Options {
     test_root_directory: String,
@@ -10,6 +16,30 @@ pub struct Options {
     use_stderr_tests: bool,
     language: String,
     compiled_test_version: bool,
+    warnings_as_failures: bool,
+    warning_filter: Vec<String>,
+    use_build_cache: bool,
+    build_command: String,
+    program_sources_directory: String,
+    toolchains: BTreeMap<String, Vec<String>>,
+    program_command: String,
+    submissions_directory: String,
+    valgrind_credit: f32,
+    warnings_credit: f32,
+    generator_path: String,
+    reference_path: String,
+    stress_iterations: u64,
+    benchmark_runs: u64,
+    benchmark_warmup: u64,
+    memory_limit_kb: u64,
+    retries: u32,
+    time_limit_factor: f32,
+    jobs: usize,
+    fail_fast: bool,
+    profile: String,
+    execution_wrapper: String,
+    #[serde(skip)]
+    provenance: config::Provenance,
 }
 
 impl Default for Options {
@@ -24,11 +54,71 @@ impl Default for Options {
             use_stderr_tests: false,
             language: "EN_en".to_string(),
             compiled_test_version: false,
+            warnings_as_failures: false,
+            warning_filter: Vec::new(),
+            use_build_cache: true,
+            build_command: String::new(),
+            program_sources_directory: String::new(),
+            toolchains: Options::default_toolchains(),
+            program_command: String::new(),
+            submissions_directory: String::new(),
+            valgrind_credit: 0.5,
+            warnings_credit: 1.0,
+            generator_path: String::new(),
+            reference_path: String::new(),
+            stress_iterations: 1000,
+            benchmark_runs: 10,
+            benchmark_warmup: 2,
+            memory_limit_kb: 0,
+            retries: 0,
+            time_limit_factor: 0.0,
+            jobs: 0,
+            fail_fast: false,
+            profile: String::new(),
+            execution_wrapper: String::new(),
+            provenance: config::Provenance::default(),
         }
     }
 }
 
 impl Options {
+    /**
+    Default toolchains of compiled tests mode. Each one maps an extension of test driver
+    to a compiler command, in which {source}, {program} and {output} are replaced by
+    path of the driver, path of tested .o file and path of produced executable.
+    */
+    fn default_toolchains() -> BTreeMap<String, Vec<String>> {
+        let gnu = |compiler: &str| {
+            let mut command = vec![compiler.to_string()];
+            command.extend(GNU_FLAGS.iter().map(|flag| flag.to_string()));
+            command.extend(
+                ["{source}", "{program}", "-o", "{output}"]
+                    .iter()
+                    .map(|arg| arg.to_string()),
+            );
+            command
+        };
+        let rust = [
+            "rustc",
+            "-O",
+            "{source}",
+            "-C",
+            "link-arg={program}",
+            "-o",
+            "{output}",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        let mut toolchains = BTreeMap::new();
+        toolchains.insert("c".to_string(), gnu("gcc"));
+        toolchains.insert("cc".to_string(), gnu("g++"));
+        toolchains.insert("cpp".to_string(), gnu("g++"));
+        toolchains.insert("rs".to_string(), rust);
+        toolchains
+    }
+
     /// Return absolute path to folder with tests
     pub fn get_test_path(&self) -> &str {
         &self.test_root_directory
@@ -54,6 +144,166 @@ impl Options {
         self.compiled_test_version
     }
 
+    /// Returns wheater compilation warnings should fail a test (true - fail)
+    pub fn get_warnings_as_failures(&self) -> bool {
+        self.warnings_as_failures
+    }
+
+    /// Returns warning flags (e.g. -Wunused-variable) which are treated as failures.
+    /// Empty list means that every warning is treated as a failure.
+    pub fn get_warning_filter(&self) -> &[String] {
+        &self.warning_filter
+    }
+
+    /// Returns wheater executables built in compiled mode are cached between runs (true - cached)
+    pub fn get_build_cache_usage(&self) -> bool {
+        self.use_build_cache
+    }
+
+    /// Returns shell command which builds the program before testing (empty - no build step)
+    pub fn get_build_command(&self) -> &str {
+        &self.build_command
+    }
+
+    /// Returns path to directory with sources of tested program (empty - not set)
+    pub fn get_sources_path(&self) -> &str {
+        &self.program_sources_directory
+    }
+
+    /// Returns compiler commands of compiled tests mode, mapped by test driver extension
+    pub fn get_toolchains(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.toolchains
+    }
+
+    /// Returns compiler command used for test drivers with given extension
+    pub fn get_toolchain(&self, extension: &str) -> Option<&[String]> {
+        self.toolchains
+            .get(extension)
+            .map(|command| command.as_slice())
+    }
+
+    /// Returns command template used to run the program (empty - program is run directly)
+    pub fn get_program_command(&self) -> &str {
+        &self.program_command
+    }
+
+    /**
+    Returns wrapper which runs tested program: "valgrind", "sanitizer" or a command put
+    before the program (empty - valgrind activity decides)
+    */
+    pub fn get_execution_wrapper(&self) -> &str {
+        &self.execution_wrapper
+    }
+
+    /// Returns wheater tested program is run through an interpreter command (true - it is)
+    pub fn is_interpreted(&self) -> bool {
+        !self.program_command.trim().is_empty()
+    }
+
+    /**
+    Returns command (program and its arguments) which runs tested program. In command
+    template {path} is replaced by program path and {dir} by directory containing it
+    ("." for a path without directory).
+    EXAMPLE: python3 {path}, java -cp {dir} Main
+    Returns configuration error when the program to run is empty (e.g. path is not set).
+    */
+    pub fn get_program_invocation(&self) -> crate::error::Result<Vec<String>> {
+        let command = if self.is_interpreted() {
+            let directory = path::Path::new(&self.program_executable_path)
+                .parent()
+                .map(|parent| parent.to_string_lossy().to_string())
+                .filter(|parent| !parent.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+
+            self.program_command
+                .split_whitespace()
+                .map(|arg| {
+                    arg.replace("{path}", &self.program_executable_path)
+                        .replace("{dir}", &directory)
+                })
+                .collect()
+        } else {
+            vec![self.program_executable_path.clone()]
+        };
+
+        match command.first() {
+            Some(program) if !program.is_empty() => Ok(command),
+            _ => Err(crate::error::Error::Config(
+                "command of tested program is empty".to_string(),
+            )),
+        }
+    }
+
+    /// Return path to folder with submissions graded in batch mode
+    pub fn get_submissions_path(&self) -> &str {
+        &self.submissions_directory
+    }
+
+    /// Returns part of points (0.0 - 1.0) given for correct output with valgrind errors
+    pub fn get_valgrind_credit(&self) -> f32 {
+        self.valgrind_credit
+    }
+
+    /// Returns part of points (0.0 - 1.0) given for passed test compiled with warnings
+    pub fn get_warnings_credit(&self) -> f32 {
+        self.warnings_credit
+    }
+
+    /// Returns path to generator of stress tests inputs
+    pub fn get_generator_path(&self) -> &str {
+        &self.generator_path
+    }
+
+    /// Returns path to reference (trusted) implementation of tested program
+    pub fn get_reference_path(&self) -> &str {
+        &self.reference_path
+    }
+
+    /// Returns maximal number of stress testing iterations
+    pub fn get_stress_iterations(&self) -> u64 {
+        self.stress_iterations
+    }
+
+    /// Returns number of measured runs of every test in benchmark mode
+    pub fn get_benchmark_runs(&self) -> u64 {
+        self.benchmark_runs
+    }
+
+    /// Returns number of warm-up runs (not measured) of every test in benchmark mode
+    pub fn get_benchmark_warmup(&self) -> u64 {
+        self.benchmark_warmup
+    }
+
+    /// Returns limit of peak memory usage of tested program in kB, 0 means no limit
+    pub fn get_memory_limit(&self) -> u64 {
+        self.memory_limit_kb
+    }
+
+    /// Returns how many times a failing test is rerun before it is marked as failed
+    pub fn get_retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Returns time limit as a multiple of runtime of the reference, 0 means no limit
+    pub fn get_time_limit_factor(&self) -> f32 {
+        self.time_limit_factor
+    }
+
+    /// Returns number of tests run at the same time, 0 means one per core
+    pub fn get_jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Returns wheater testing stops scheduling new tests after the first failure
+    pub fn get_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Returns name of the selected profile, empty when no profile is used
+    pub fn get_profile(&self) -> &str {
+        &self.profile
+    }
+
     /// Function checkes wheter path points to a directory, if so, it sets
     /// path of test folder accordingly and return true, otherwise it return false
     pub fn set_test_path(&mut self, path: &str) -> bool {
@@ -88,6 +338,162 @@ impl Options {
         self.compiled_test_version = option;
     }
 
+    /// Sets wheter compilation warnings should fail a test (true - fail)
+    pub fn set_warnings_as_failures(&mut self, option: bool) {
+        self.warnings_as_failures = option;
+    }
+
+    /// Sets warning flags which are treated as failures, empty list means every warning
+    pub fn set_warning_filter(&mut self, filter: Vec<String>) {
+        self.warning_filter = filter;
+    }
+
+    /// Sets wheter executables built in compiled mode are cached between runs (true - cached)
+    pub fn set_build_cache_usage(&mut self, option: bool) {
+        self.use_build_cache = option;
+    }
+
+    /// Sets shell command which builds the program before testing, empty command disables it
+    pub fn set_build_command(&mut self, command: &str) {
+        self.build_command = command.to_string();
+    }
+
+    /// Function checkes wheter path is empty or points to a directory, if so, it sets
+    /// path of program sources accordingly and return true, otherwise it return false
+    pub fn set_sources_path(&mut self, path: &str) -> bool {
+        if path.is_empty() || path::Path::new(path).is_dir() {
+            self.program_sources_directory = path.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets compiler command used for test drivers with given extension,
+    /// empty command removes the toolchain
+    pub fn set_toolchain(&mut self, extension: &str, command: Vec<String>) {
+        if command.is_empty() {
+            self.toolchains.remove(extension);
+        } else {
+            self.toolchains.insert(extension.to_string(), command);
+        }
+    }
+
+    /// Sets wrapper which runs tested program, empty one leaves it to valgrind activity
+    pub fn set_execution_wrapper(&mut self, wrapper: &str) {
+        self.execution_wrapper = wrapper.to_string();
+    }
+
+    /// Sets command template used to run the program, empty template runs it directly
+    pub fn set_program_command(&mut self, command: &str) {
+        self.program_command = command.to_string();
+    }
+
+    /// Function checkes wheter path points to a directory, if so, it sets path of
+    /// submissions folder accordingly and return true, otherwise it return false
+    pub fn set_submissions_path(&mut self, path: &str) -> bool {
+        if path::Path::new(path).is_dir() {
+            self.submissions_directory = path.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets part of points given for correct output with valgrind errors,
+    /// returns false if it is not in 0.0 - 1.0 range
+    pub fn set_valgrind_credit(&mut self, credit: f32) -> bool {
+        if (0.0..=1.0).contains(&credit) {
+            self.valgrind_credit = credit;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets part of points given for passed test compiled with warnings,
+    /// returns false if it is not in 0.0 - 1.0 range
+    pub fn set_warnings_credit(&mut self, credit: f32) -> bool {
+        if (0.0..=1.0).contains(&credit) {
+            self.warnings_credit = credit;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Function checkes wheter path points to a file, if so, it sets
+    /// path of stress tests generator accordingly and return true, otherwise it return false
+    pub fn set_generator_path(&mut self, path: &str) -> bool {
+        if path::Path::new(path).is_file() {
+            self.generator_path = path.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Function checkes wheter path points to a file, if so, it sets path of
+    /// reference implementation accordingly and return true, otherwise it return false
+    pub fn set_reference_path(&mut self, path: &str) -> bool {
+        if path::Path::new(path).is_file() {
+            self.reference_path = path.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets maximal number of stress testing iterations
+    pub fn set_stress_iterations(&mut self, iterations: u64) {
+        self.stress_iterations = iterations;
+    }
+
+    /// Sets number of measured runs in benchmark mode, at least one run is needed
+    pub fn set_benchmark_runs(&mut self, runs: u64) -> bool {
+        if runs > 0 {
+            self.benchmark_runs = runs;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets number of warm-up runs in benchmark mode
+    pub fn set_benchmark_warmup(&mut self, runs: u64) {
+        self.benchmark_warmup = runs;
+    }
+
+    /// Sets limit of peak memory usage in kB, 0 turns the limit off
+    pub fn set_memory_limit(&mut self, limit_kb: u64) {
+        self.memory_limit_kb = limit_kb;
+    }
+
+    /// Sets how many times a failing test is rerun
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    /// Sets time limit factor, returns false when it is negative
+    pub fn set_time_limit_factor(&mut self, factor: f32) -> bool {
+        if factor >= 0.0 {
+            self.time_limit_factor = factor;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets number of tests run at the same time, 0 means one per core
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = jobs;
+    }
+
+    /// Sets wheter testing stops after the first failure (true - stops)
+    pub fn set_fail_fast(&mut self, option: bool) {
+        self.fail_fast = option;
+    }
+
     /// Sets wheter to use stderr tests in testing process (true - use)
     pub fn set_stderr_usage(&mut self, option: bool) {
         self.use_stderr_tests = option;
@@ -102,28 +508,62 @@ impl Options {
         &self.program_executable_path
     }
 
-    /// If correct config.json exists in main folder, it generates an Options structure
-    /// which is saved in that file. Otherwise it generate default structure.
-    pub fn new() -> Options {
-        if path::Path::new("config.json").exists() {
-            let content = fs::read_to_string("config.json")
-                .expect("ERROR: Opening folder with settings FAILED.");
-
-            let preferences = serde_json::from_str(&content);
-            if preferences.is_err() {
-                return Options::default();
-            }
+    /**
+    Loads settings from all layers of configuration (defaults, user config, config.json,
+    project .rtest.toml, selected profile, environment variables and given command line overrides).
+    */
+    pub fn new(overrides: &[(String, String)]) -> crate::error::Result<Options> {
+        config::load(overrides, None)
+    }
 
-            preferences.unwrap()
-        } else {
-            Options::default()
+    /// Saves settings changed in menu to project configuration file
+    pub fn save(self) {
+        if let Err(e) = config::save(&self) {
+            eprintln!("ERROR: Saving configuration FAILED. ({})", e);
         }
     }
+}
 
-    /// Function saves config structure to config.json file
-    pub fn save(self) {
-        let content = serde_json::to_string_pretty(&self)
-            .expect("ERROR: Creating JSON description of options FAILED.");
-        let _ = fs::write("config.json", &content);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn program_invocation_test() {
+        let mut options = Options {
+            program_executable_path: "/home/user/solution/main.py".to_string(),
+            ..Options::default()
+        };
+        assert_eq!(
+            options.get_program_invocation().unwrap(),
+            ["/home/user/solution/main.py"]
+        );
+
+        options.set_program_command("  ");
+        assert_eq!(
+            options.get_program_invocation().unwrap(),
+            ["/home/user/solution/main.py"]
+        );
+
+        options.set_program_command("python3 {path} --root {dir}");
+        assert_eq!(
+            options.get_program_invocation().unwrap(),
+            [
+                "python3",
+                "/home/user/solution/main.py",
+                "--root",
+                "/home/user/solution"
+            ]
+        );
+
+        options.program_executable_path = "main.py".to_string();
+        assert_eq!(
+            options.get_program_invocation().unwrap(),
+            ["python3", "main.py", "--root", "."]
+        );
+
+        options.set_program_command("");
+        options.program_executable_path = String::new();
+        assert!(options.get_program_invocation().is_err());
     }
 }
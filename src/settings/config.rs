@@ -0,0 +1,503 @@
+//! Layered configuration. Settings are built from (in order of increasing priority):
+//! built-in defaults, user config in XDG config home, legacy config.json in working directory,
+//! project .rtest.toml found by walking up from working directory, RTEST_* environment
+//! variables and --set flags given on command line. Source of every value is remembered.
+//! Config files may define named profiles ([profiles.NAME] tables, which can inherit
+//! from each other with `inherits = "OTHER"`), the selected one is applied over the files.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::value::Table;
+use toml::Value;
+
+use super::Options;
+use crate::error::{self, Error};
+
+/// Name of project configuration file
+pub const PROJECT_FILE: &str = ".rtest.toml";
+/// Legacy configuration file, read from working directory
+const LEGACY_FILE: &str = "config.json";
+/// Key of config files holding profiles, it is not a setting
+const PROFILES_KEY: &str = "profiles";
+/// Key of profile naming profile it inherits from
+const INHERITS_KEY: &str = "inherits";
+/// Prefix of environment variables overriding settings (e.g. RTEST_JOBS=4)
+const ENV_PREFIX: &str = "RTEST_";
+
+/// Settings which hold paths, relative ones in files are resolved against folder of the file
+const PATH_KEYS: [&str; 6] = [
+    "test_root_directory",
+    "program_executable_path",
+    "program_sources_directory",
+    "submissions_directory",
+    "generator_path",
+    "reference_path",
+];
+
+/// Layer of configuration, which gave a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    UserFile(PathBuf),
+    LegacyFile(PathBuf),
+    ProjectFile(PathBuf),
+    Profile(String),
+    Environment(String),
+    CommandLine,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "defaults"),
+            Source::UserFile(path) | Source::LegacyFile(path) | Source::ProjectFile(path) => {
+                write!(f, "{}", path.display())
+            }
+            Source::Profile(name) => write!(f, "profile {}", name),
+            Source::Environment(variable) => write!(f, "${}", variable),
+            Source::CommandLine => write!(f, "--set"),
+        }
+    }
+}
+
+/// Sources of values and state needed to save changes made in menu
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    origins: BTreeMap<String, Source>,
+    loaded: Table,
+    project_file: Option<PathBuf>,
+    /// Profiles defined in all config files
+    profiles: Table,
+    /// Settings given on command line, kept to load settings again with another profile
+    overrides: Vec<(String, String)>,
+}
+
+/// Returns settings as a TOML table
+fn to_table(options: &Options) -> error::Result<Table> {
+    match Value::try_from(options) {
+        Ok(Value::Table(table)) => Ok(table),
+        Ok(_) => Err(Error::Config("settings are not a TOML table".to_string())),
+        Err(e) => Err(Error::Config(e.to_string())),
+    }
+}
+
+/// Path of user configuration file ($XDG_CONFIG_HOME/rtest/config.toml)
+fn user_file() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("rtest").join("config.toml"))
+}
+
+/// Finds project configuration file in working directory or the closest of its parents
+pub fn find_project_file() -> Option<PathBuf> {
+    let current = env::current_dir().ok()?;
+    current
+        .ancestors()
+        .map(|directory| directory.join(PROJECT_FILE))
+        .find(|file| file.is_file())
+}
+
+/// Reads a configuration file (TOML or JSON by extension) as a table
+fn read_file(path: &Path) -> Result<Table, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut table: Table = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        match Value::try_from(json) {
+            Ok(Value::Table(table)) => table,
+            _ => return Err("configuration is not an object".to_string()),
+        }
+    } else {
+        toml::from_str(&content).map_err(|e| e.to_string())?
+    };
+
+    // Relative paths are relative to the file, so the project can be used from any subfolder.
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+    resolve_paths(&mut table, directory);
+    if let Some(Value::Table(profiles)) = table.get_mut(PROFILES_KEY) {
+        for (_, profile) in profiles.iter_mut() {
+            if let Value::Table(profile) = profile {
+                resolve_paths(profile, directory);
+            }
+        }
+    }
+    Ok(table)
+}
+
+/// Makes relative paths in settings of table relative to given directory
+fn resolve_paths(table: &mut Table, directory: &Path) {
+    for key in PATH_KEYS {
+        if let Some(Value::String(value)) = table.get_mut(key) {
+            if !value.is_empty() && Path::new(value.as_str()).is_relative() {
+                *value = directory.join(&value).to_string_lossy().to_string();
+            }
+        }
+    }
+}
+
+/**
+Returns settings of a profile together with settings of profiles it inherits from
+(settings of the profile itself take precedence).
+*/
+fn resolve_profile(profiles: &Table, name: &str) -> Result<Table, String> {
+    let mut chain: Vec<&Table> = Vec::new();
+    let mut visited: Vec<&str> = Vec::new();
+    let mut current = name;
+
+    loop {
+        if visited.contains(&current) {
+            return Err(format!("profile '{}' inherits from itself", current));
+        }
+        let profile = profiles
+            .get(current)
+            .and_then(Value::as_table)
+            .ok_or_else(|| format!("unknown profile '{}'", current))?;
+        visited.push(current);
+        chain.push(profile);
+
+        match profile.get(INHERITS_KEY) {
+            None => break,
+            Some(Value::String(parent)) => current = parent,
+            Some(_) => {
+                return Err(format!(
+                    "'{}' of profile '{}' is not a name",
+                    INHERITS_KEY, current
+                ))
+            }
+        }
+    }
+
+    let mut table = Table::new();
+    for profile in chain.into_iter().rev() {
+        merge(&mut table, profile);
+    }
+    table.remove(INHERITS_KEY);
+    Ok(table)
+}
+
+/**
+Parses value of a setting given as text (in environment variable or on command line).
+Type of value is taken from defaults: text settings take it as it is, lists are split by commas
+and the other ones are parsed as TOML values.
+*/
+pub fn parse_value(key: &str, raw: &str) -> Result<Value, String> {
+    let defaults = to_table(&Options::default()).map_err(|e| e.to_string())?;
+    match defaults.get(key) {
+        None => Err(format!("unknown setting '{}'", key)),
+        Some(Value::String(_)) => Ok(Value::String(raw.to_string())),
+        Some(Value::Array(_)) => Ok(Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        )),
+        Some(_) => {
+            let parsed: Table =
+                toml::from_str(&format!("value = {}", raw)).map_err(|e| e.to_string())?;
+            parsed
+                .get("value")
+                .cloned()
+                .ok_or_else(|| format!("missing value of '{}'", key))
+        }
+    }
+}
+
+/// Puts values of layer over the table, nested tables (toolchains) are merged key by key
+fn merge(table: &mut Table, layer: &Table) {
+    for (key, value) in layer {
+        match (table.get_mut(key), value) {
+            (Some(Value::Table(current)), Value::Table(nested)) => merge(current, nested),
+            _ => {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Builder of settings, which applies layers one after another
+struct Layers {
+    table: Table,
+    origins: BTreeMap<String, Source>,
+}
+
+impl Layers {
+    /// Applies a layer, which is skipped (with a warning) when it gives values of wrong types
+    fn apply(&mut self, layer: Table, source: Source) {
+        let mut merged = self.table.clone();
+        merge(&mut merged, &layer);
+        if let Err(e) = Value::Table(merged.clone()).try_into::<Options>() {
+            eprintln!("WARNING: Configuration from {} IGNORED. ({})", source, e);
+            return;
+        }
+
+        for key in layer.keys() {
+            self.origins.insert(key.clone(), source.clone());
+        }
+        self.table = merged;
+    }
+}
+
+/// Makes a layer of a single setting given as text, source describes where it was given
+fn text_layer(key: &str, raw: &str, source: &Source) -> Option<Table> {
+    match parse_value(key, raw) {
+        Ok(value) => Some(Table::from_iter([(key.to_string(), value)])),
+        Err(e) => {
+            eprintln!(
+                "WARNING: Setting {}={} from {} IGNORED. ({})",
+                key, raw, source, e
+            );
+            None
+        }
+    }
+}
+
+/**
+Loads settings from all layers. Overrides are pairs of setting name and its value
+given on command line. Profile chosen in menu takes precedence over the one in settings.
+*/
+pub fn load(
+    overrides: &[(String, String)],
+    chosen_profile: Option<&str>,
+) -> error::Result<Options> {
+    let defaults = to_table(&Options::default())?;
+    let mut layers = Layers {
+        origins: defaults
+            .keys()
+            .map(|key| (key.clone(), Source::Default))
+            .collect(),
+        table: defaults.clone(),
+    };
+
+    let mut files = Vec::new();
+    if let Some(path) = user_file().filter(|path| path.is_file()) {
+        files.push((path.clone(), Source::UserFile(path)));
+    }
+    let legacy = PathBuf::from(LEGACY_FILE);
+    if legacy.is_file() {
+        files.push((legacy.clone(), Source::LegacyFile(legacy)));
+    }
+    let project_file = find_project_file();
+    if let Some(path) = &project_file {
+        files.push((path.clone(), Source::ProjectFile(path.clone())));
+    }
+
+    let mut profiles = Table::new();
+    for (path, source) in files {
+        match read_file(&path) {
+            Ok(mut layer) => {
+                if let Some(Value::Table(defined)) = layer.remove(PROFILES_KEY) {
+                    merge(&mut profiles, &defined);
+                }
+                layers.apply(layer, source);
+            }
+            Err(e) => eprintln!(
+                "WARNING: Reading configuration {} FAILED. ({})",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    // Environment and command line are applied over the profile, but they can choose it.
+    let mut later = Vec::new();
+    for key in defaults.keys() {
+        let variable = format!("{}{}", ENV_PREFIX, key.to_uppercase());
+        if let Ok(raw) = env::var(&variable) {
+            let source = Source::Environment(variable);
+            if let Some(layer) = text_layer(key, &raw, &source) {
+                later.push((layer, source));
+            }
+        }
+    }
+    for (key, raw) in overrides {
+        if let Some(layer) = text_layer(key, raw, &Source::CommandLine) {
+            later.push((layer, Source::CommandLine));
+        }
+    }
+
+    let profile = match chosen_profile {
+        Some(profile) => profile.to_string(),
+        None => later
+            .iter()
+            .rev()
+            .map(|(layer, _)| layer)
+            .chain([&layers.table])
+            .find_map(|layer| layer.get("profile").and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string(),
+    };
+    if !profile.is_empty() {
+        match resolve_profile(&profiles, &profile) {
+            Ok(layer) => layers.apply(layer, Source::Profile(profile.clone())),
+            Err(e) => eprintln!("WARNING: Profile {} IGNORED. ({})", profile, e),
+        }
+    }
+
+    for (layer, source) in later {
+        layers.apply(layer, source);
+    }
+    if let Some(profile) = chosen_profile {
+        layers
+            .table
+            .insert("profile".to_string(), Value::String(profile.to_string()));
+    }
+
+    let mut options: Options = Value::Table(layers.table.clone())
+        .try_into()
+        .map_err(|e: toml::de::Error| Error::Config(e.to_string()))?;
+    if let Some((extension, _)) = options.get_toolchains().iter().find(|(_, c)| c.is_empty()) {
+        return Err(Error::Config(format!(
+            "toolchain for .{} files is empty",
+            extension
+        )));
+    }
+    options.provenance = Provenance {
+        origins: layers.origins,
+        loaded: layers.table,
+        project_file,
+        profiles,
+        overrides: overrides.to_vec(),
+    };
+    Ok(options)
+}
+
+/// Returns names of profiles defined in config files
+pub fn profile_names(options: &Options) -> Vec<String> {
+    options.provenance.profiles.keys().cloned().collect()
+}
+
+/**
+Loads settings again with another profile (empty name - no profile). Settings changed
+in menu before stay changed and the chosen profile is saved together with them.
+*/
+pub fn switch_profile(options: &mut Options, name: &str) -> error::Result<()> {
+    let provenance = &options.provenance;
+    if !name.is_empty() {
+        resolve_profile(&provenance.profiles, name).map_err(Error::Config)?;
+    }
+
+    let changed: Table = to_table(options)?
+        .into_iter()
+        .filter(|(key, value)| key != "profile" && provenance.loaded.get(key) != Some(value))
+        .collect();
+    let mut switched = load(&provenance.overrides, Some(name))?;
+
+    let mut table = to_table(&switched)?;
+    table.extend(changed.clone());
+    let mut provenance = switched.provenance;
+    switched = Value::Table(table)
+        .try_into()
+        .map_err(|e: toml::de::Error| Error::Config(e.to_string()))?;
+
+    // Values compared on saving are the old ones, so the changes are still saved.
+    for key in changed.keys().map(String::as_str).chain(["profile"]) {
+        match options.provenance.loaded.get(key) {
+            Some(value) => provenance.loaded.insert(key.to_string(), value.clone()),
+            None => provenance.loaded.remove(key),
+        };
+    }
+    switched.provenance = provenance;
+    *options = switched;
+    Ok(())
+}
+
+/**
+Saves settings changed since loading to project file (the one which was loaded, or a new
+.rtest.toml in working directory). Values which were not changed stay in their layers.
+*/
+pub fn save(options: &Options) -> std::io::Result<()> {
+    let provenance = &options.provenance;
+    let changed: Table = to_table(options)
+        .map_err(std::io::Error::other)?
+        .into_iter()
+        .filter(|(key, value)| provenance.loaded.get(key) != Some(value))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let path = provenance
+        .project_file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(PROJECT_FILE));
+    // Project file is read as it is, so values and keys not known to options are kept.
+    let mut project: Table = match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).map_err(std::io::Error::other)?,
+        Err(_) => Table::new(),
+    };
+    project.extend(changed);
+
+    let content = toml::to_string_pretty(&Value::Table(project)).map_err(std::io::Error::other)?;
+    fs::write(path, content)
+}
+
+/// Returns every setting with its effective value and layer which gave it
+pub fn describe(options: &Options) -> error::Result<Vec<(String, String, Source)>> {
+    Ok(to_table(options)?
+        .into_iter()
+        .map(|(key, value)| {
+            let source = options
+                .provenance
+                .origins
+                .get(&key)
+                .cloned()
+                .unwrap_or(Source::Default);
+            (key, value.to_string(), source)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_value_test() {
+        assert_eq!(parse_value("jobs", "4"), Ok(Value::Integer(4)));
+        assert_eq!(
+            parse_value("build_command", "make all"),
+            Ok(Value::String("make all".to_string()))
+        );
+        assert_eq!(
+            parse_value("warning_filter", "unused, shadow"),
+            Ok(Value::Array(vec![
+                Value::String("unused".to_string()),
+                Value::String("shadow".to_string())
+            ]))
+        );
+        assert!(parse_value("no_such_setting", "1").is_err());
+    }
+
+    #[test]
+    fn resolve_profile_test() {
+        let profiles: Table = toml::from_str(
+            r#"
+            [base]
+            jobs = 2
+            retries = 1
+            [full]
+            inherits = "base"
+            retries = 3
+            [loop]
+            inherits = "loop"
+            "#,
+        )
+        .unwrap();
+
+        let full = resolve_profile(&profiles, "full").unwrap();
+        assert_eq!(full.get("jobs"), Some(&Value::Integer(2)));
+        assert_eq!(full.get("retries"), Some(&Value::Integer(3)));
+        assert!(full.get(INHERITS_KEY).is_none());
+        assert!(resolve_profile(&profiles, "loop").is_err());
+        assert!(resolve_profile(&profiles, "missing").is_err());
+    }
+}
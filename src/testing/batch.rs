@@ -0,0 +1,290 @@
+//! Batch grading of many submissions (one executable or .o file each) against one test suite.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use super::cancel;
+use super::history::History;
+use super::manifest::Manifest;
+use super::scheduler;
+use super::scoring::{self, Score};
+use super::time_limit;
+use super::{load_tests, run_test_with_retries, Pipeline, TestResult};
+use crate::settings::Options;
+
+/// Results of a single submission
+pub struct Submission {
+    name: String,
+    results: Vec<TestResult>,
+    score: Score,
+}
+
+impl Submission {
+    /// Returns file name of the submission
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns results of the submission, in the same order as gradebook tests
+    pub fn get_results(&self) -> &[TestResult] {
+        &self.results
+    }
+
+    /// Returns weighted score of the submission
+    pub fn get_score(&self) -> Score {
+        self.score
+    }
+
+    /// Returns number of passed tests
+    pub fn passed_count(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+}
+
+/// Gradebook of submissions versus tests
+pub struct Gradebook {
+    tests: Vec<String>,
+    submissions: Vec<Submission>,
+}
+
+#[derive(serde::Serialize)]
+struct JsonResult {
+    passed: bool,
+    flaky: bool,
+    problem: Option<String>,
+    exit_code: i32,
+    time: f32,
+}
+
+#[derive(serde::Serialize)]
+struct JsonSubmission<'a> {
+    name: &'a str,
+    passed: usize,
+    total: usize,
+    score: f32,
+    max_score: f32,
+    results: BTreeMap<String, JsonResult>,
+}
+
+#[derive(serde::Serialize)]
+struct JsonGradebook<'a> {
+    tests: &'a [String],
+    submissions: Vec<JsonSubmission<'a>>,
+}
+
+impl Gradebook {
+    /// Returns names of tests, which are columns of gradebook
+    pub fn get_tests(&self) -> &[String] {
+        &self.tests
+    }
+
+    /// Returns graded submissions, sorted by name
+    pub fn get_submissions(&self) -> &[Submission] {
+        &self.submissions
+    }
+
+    /**
+    Creates CSV version of gradebook. Each row describes one submission, each test
+    column holds PASSED or problem title of a failed test.
+    */
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["submission".to_string()];
+        header.extend(self.tests.iter().cloned());
+        header.push("passed".to_string());
+        header.push("total".to_string());
+        header.push("score".to_string());
+        header.push("max_score".to_string());
+
+        let mut content = csv_row(&header);
+        for submission in &self.submissions {
+            let mut row = vec![submission.name.clone()];
+            row.extend(submission.results.iter().map(|result| {
+                if result.passed() {
+                    "PASSED".to_string()
+                } else {
+                    result.get_problem_description()
+                }
+            }));
+            row.push(submission.passed_count().to_string());
+            row.push(submission.results.len().to_string());
+            row.push(submission.score.earned.to_string());
+            row.push(submission.score.maximum.to_string());
+            content.push_str(&csv_row(&row));
+        }
+        content
+    }
+
+    /// Creates JSON version of gradebook
+    pub fn to_json(&self) -> io::Result<String> {
+        let submissions = self
+            .submissions
+            .iter()
+            .map(|submission| JsonSubmission {
+                name: &submission.name,
+                passed: submission.passed_count(),
+                total: submission.results.len(),
+                score: submission.score.earned,
+                max_score: submission.score.maximum,
+                results: submission
+                    .results
+                    .iter()
+                    .map(|result| {
+                        let entry = JsonResult {
+                            passed: result.passed(),
+                            flaky: result.flaky(),
+                            problem: (!result.passed()).then(|| result.get_problem_description()),
+                            exit_code: result.get_exit_code(),
+                            time: result.get_time(),
+                        };
+                        (result.get_name(), entry)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let gradebook = JsonGradebook {
+            tests: &self.tests,
+            submissions,
+        };
+        serde_json::to_string_pretty(&gradebook).map_err(io::Error::other)
+    }
+
+    /// Saves gradebook to gradebook.csv and gradebook.json files in main directory
+    pub fn save(&self) -> io::Result<()> {
+        fs::write("gradebook.csv", self.to_csv())?;
+        fs::write("gradebook.json", self.to_json()?)
+    }
+}
+
+/**
+Returns sorted paths of submissions (every file) in submissions folder from settings.
+*/
+fn load_submissions(settings: &Options) -> io::Result<Vec<String>> {
+    let mut submissions = Vec::new();
+    for entry in fs::read_dir(settings.get_submissions_path())? {
+        let path = entry?.path();
+        if path.is_file() {
+            submissions.push(path.to_string_lossy().to_string());
+        }
+    }
+    submissions.sort();
+    Ok(submissions)
+}
+
+/**
+Runs whole test suite on every submission. All tests of all submissions are
+scheduled together, so they share one pool of threads.
+*/
+pub fn run_batch(settings: &Options) -> io::Result<Gradebook> {
+    let submissions = load_submissions(settings)?;
+
+    let configurations: Vec<Options> = submissions
+        .iter()
+        .map(|submission| {
+            let mut configuration = settings.clone();
+            configuration.set_program_path(submission);
+            configuration
+        })
+        .collect();
+
+    let mut jobs: Vec<(usize, TestResult)> = Vec::new();
+    for (submission, configuration) in configurations.iter().enumerate() {
+        jobs.extend(
+            load_tests(configuration)
+                .map_err(io::Error::other)?
+                .into_iter()
+                .map(|test| (submission, test)),
+        );
+    }
+
+    // Steps read program path from settings of a submission, so all of them share one pipeline.
+    let pipeline = Pipeline::new(settings);
+    // Reference runtimes depend only on test inputs, so they are the same for every submission.
+    time_limit::set_time_limits(jobs.iter_mut().map(|(_, test)| test), settings, &pipeline)?;
+    let manifest = Manifest::load(settings.get_test_path());
+    let run = cancel::Run::start();
+    scheduler::sort_slowest_first(&mut jobs, &History::load(), |(_, test)| test);
+    scheduler::schedule(
+        &mut jobs,
+        settings,
+        |(_, test)| manifest.entry(&test.get_name()).exclusive,
+        |(submission, frame), index| {
+            run_test_with_retries(frame, &pipeline, &configurations[*submission], index);
+            false
+        },
+    )
+    .map_err(io::Error::other)?;
+    drop(run);
+    if cancel::is_interrupted() {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "batch grading was interrupted",
+        ));
+    }
+
+    let tests: Vec<String> = load_tests(settings)
+        .map_err(io::Error::other)?
+        .iter()
+        .map(|test| test.get_name())
+        .collect();
+
+    let mut graded: Vec<Submission> = submissions
+        .iter()
+        .map(|path| Submission {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            results: Vec::new(),
+            score: Score {
+                earned: 0.0,
+                maximum: 0.0,
+            },
+        })
+        .collect();
+    for (submission, result) in jobs {
+        graded[submission].results.push(result);
+    }
+    for (submission, configuration) in graded.iter_mut().zip(&configurations) {
+        submission.results.sort();
+        submission.score =
+            scoring::suite_score(&submission.results, &tests, &manifest, configuration);
+    }
+
+    Ok(Gradebook {
+        tests,
+        submissions: graded,
+    })
+}
+
+/// Formats one CSV row, quoting fields which contain separators, quotes or newlines
+fn csv_row(fields: &[String]) -> String {
+    let escaped: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+
+    let mut row = escaped.join(",");
+    row.push('\n');
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_row_test() {
+        let row = csv_row(&[
+            "plain".to_string(),
+            "with,comma".to_string(),
+            "say \"hi\"".to_string(),
+        ]);
+
+        assert_eq!(row, "plain,\"with,comma\",\"say \"\"hi\"\"\"\n");
+    }
+}
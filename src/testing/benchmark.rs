@@ -0,0 +1,189 @@
+//! Benchmark mode: every test is run many times after warm-up runs and only the
+//! child process is measured, so results can be used to compare performance.
+
+use indicatif::ProgressBar;
+use std::fs;
+use std::io;
+
+use super::cancel;
+use super::measure::{self, Measurement};
+use super::{load_tests, TestResult};
+use crate::error;
+use crate::settings::Options;
+
+/// Statistics of measured samples (in seconds)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Number of samples outside of Tukey's fences (1.5 IQR from quartiles)
+    pub outliers: usize,
+}
+
+/// Returns value of given quantile of sorted samples, with linear interpolation
+fn quantile(sorted: &[f64], quantile: f64) -> f64 {
+    let position = quantile * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+impl Statistics {
+    /// Computes statistics of given samples, there has to be at least one sample
+    pub fn of(samples: &[f64]) -> Statistics {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let count = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / count;
+        let variance = sorted
+            .iter()
+            .map(|sample| (sample - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        let first_quartile = quantile(&sorted, 0.25);
+        let third_quartile = quantile(&sorted, 0.75);
+        let fence = 1.5 * (third_quartile - first_quartile);
+        let outliers = sorted
+            .iter()
+            .filter(|&&sample| sample < first_quartile - fence || sample > third_quartile + fence)
+            .count();
+
+        Statistics {
+            min: sorted[0],
+            median: quantile(&sorted, 0.5),
+            mean,
+            stddev: variance.sqrt(),
+            outliers,
+        }
+    }
+}
+
+/// Benchmark of a single test
+#[derive(Debug)]
+pub struct BenchmarkResult {
+    name: String,
+    outcome: Result<Timings, String>,
+}
+
+/// Statistics of wall time and CPU times of measured runs
+#[derive(Debug, Clone, Copy)]
+pub struct Timings {
+    pub wall: Statistics,
+    pub user: Statistics,
+    pub system: Statistics,
+}
+
+impl BenchmarkResult {
+    /// Returns a name of benchmarked test
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns timings of the test or description of a problem which stopped benchmark
+    pub fn get_outcome(&self) -> &Result<Timings, String> {
+        &self.outcome
+    }
+}
+
+/**
+Runs a single test warm-up + runs times and returns timings of runs after warm-up.
+In compiled mode test driver is compiled once, before the first run.
+*/
+fn benchmark_test(
+    test: &mut TestResult,
+    settings: &Options,
+    index: usize,
+    progress: &ProgressBar,
+) -> io::Result<Timings> {
+    let input = fs::read(test.get_test_path())?;
+
+    let compiled = if settings.get_program_mode() {
+        match test.compile_program(settings, index) {
+            Some(compiled) => Some(compiled),
+            None => return Err(io::Error::other(test.get_problem().to_string())),
+        }
+    } else {
+        None
+    };
+    let command = match &compiled {
+        Some(compiled) => vec![format!("./{}", compiled)],
+        None => settings
+            .get_program_invocation()
+            .map_err(io::Error::other)?,
+    };
+
+    let warmup = settings.get_benchmark_warmup();
+    let mut measurements: Vec<Measurement> = Vec::new();
+    let mut outcome = Ok(());
+    for run in 0..warmup + settings.get_benchmark_runs() {
+        match measure::run_measured(&command, &input)
+            .and_then(|measured| cancel::check_interrupted().map(|_| measured))
+        {
+            Ok((_, measurement)) if run >= warmup => measurements.push(measurement),
+            Ok(_) => {}
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+        progress.inc(1);
+    }
+
+    if let Some(compiled) = compiled {
+        TestResult::remove_compiled(&compiled);
+    }
+    outcome?;
+
+    let samples =
+        |value: fn(&Measurement) -> f64| -> Vec<f64> { measurements.iter().map(value).collect() };
+    Ok(Timings {
+        wall: Statistics::of(&samples(|measurement| measurement.wall)),
+        user: Statistics::of(&samples(|measurement| measurement.user)),
+        system: Statistics::of(&samples(|measurement| measurement.system)),
+    })
+}
+
+/**
+Benchmarks every test with number of runs and warm-up runs from settings.
+Tests are run one after another, as parallel runs would disturb each other's timings.
+When interrupted with Ctrl+C, only tests started before are returned.
+*/
+pub fn run_benchmark(settings: &Options) -> error::Result<Vec<BenchmarkResult>> {
+    let mut tests = load_tests(settings)?;
+    let runs = settings.get_benchmark_warmup() + settings.get_benchmark_runs();
+    let progress = ProgressBar::new(tests.len() as u64 * runs);
+    let _run = cancel::Run::start();
+
+    let results = tests
+        .iter_mut()
+        .enumerate()
+        .take_while(|_| !cancel::is_stopped())
+        .map(|(index, test)| BenchmarkResult {
+            name: test.get_name(),
+            outcome: benchmark_test(test, settings, index, &progress).map_err(|e| e.to_string()),
+        })
+        .collect();
+
+    progress.finish_and_clear();
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_test() {
+        let statistics = Statistics::of(&[3.0, 1.0, 2.0, 4.0, 100.0]);
+
+        assert_eq!(statistics.min, 1.0);
+        assert_eq!(statistics.median, 3.0);
+        assert_eq!(statistics.mean, 22.0);
+        assert_eq!(statistics.outliers, 1);
+        assert!((statistics.stddev - 1522.0_f64.sqrt()).abs() < 1e-9);
+    }
+}
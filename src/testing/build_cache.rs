@@ -0,0 +1,195 @@
+//! Cache of executables built in compiled tests mode. Every executable is kept under a key
+//! computed from contents of compiled files (with local headers they include), compiler and
+//! its flags, so unchanged test drivers are not compiled again on later runs. Only the latest
+//! executable of every test driver and program is kept.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to working directory) where cached executables are stored
+pub const CACHE_DIR: &str = ".rtest_cache";
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/**
+Hashes given byte chunks with 64-bit FNV-1a. Length of every chunk is hashed too,
+so moving bytes between neighbouring chunks changes the result.
+*/
+pub fn hash_chunks(chunks: &[&[u8]]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    for chunk in chunks {
+        feed(&(chunk.len() as u64).to_le_bytes());
+        feed(chunk);
+    }
+    hash
+}
+
+/// Returns local headers (`#include "..."`) named in source text
+fn included_headers(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter_map(|line| line.trim_start().strip_prefix('#'))
+        .filter_map(|line| line.trim_start().strip_prefix("include"))
+        .filter_map(|line| line.trim_start().strip_prefix('"'))
+        .filter_map(|line| line.split('"').next())
+        .collect()
+}
+
+/**
+Collects local headers included by given file, directly or through other headers. Headers
+are looked up next to the including file, the ones which are not found there are skipped.
+*/
+fn collect_headers(file: &Path, headers: &mut BTreeSet<PathBuf>) {
+    let text = match fs::read(file) {
+        Ok(content) => String::from_utf8_lossy(&content).to_string(),
+        Err(_) => return,
+    };
+    let directory = file.parent().unwrap_or(Path::new(""));
+
+    for header in included_headers(&text) {
+        let path = directory.join(header);
+        if path.is_file() && headers.insert(path.clone()) {
+            collect_headers(&path, headers);
+        }
+    }
+}
+
+/**
+Computes a cache key of an executable built by given compiler command from given files
+and local headers they include.
+*/
+pub fn cache_key(files: &[&str], command: &[String]) -> io::Result<String> {
+    let mut headers = BTreeSet::new();
+    for file in files {
+        collect_headers(Path::new(file), &mut headers);
+    }
+
+    let mut contents = Vec::with_capacity(files.len() + headers.len());
+    for file in files {
+        contents.push(fs::read(file)?);
+    }
+    for header in &headers {
+        contents.push(header.to_string_lossy().as_bytes().to_vec());
+        contents.push(fs::read(header)?);
+    }
+
+    let mut chunks: Vec<&[u8]> = contents.iter().map(|content| content.as_slice()).collect();
+    chunks.extend(command.iter().map(|arg| arg.as_bytes()));
+
+    Ok(format!("{:016x}", hash_chunks(&chunks)))
+}
+
+fn binary_path(key: &str) -> String {
+    format!("{}/{}", CACHE_DIR, key)
+}
+
+fn warnings_path(key: &str) -> String {
+    format!("{}/{}.warnings", CACHE_DIR, key)
+}
+
+/// Path of file holding the latest key of executables built for given owner
+fn latest_path(owner: &str) -> String {
+    format!(
+        "{}/{:016x}.latest",
+        CACHE_DIR,
+        hash_chunks(&[owner.as_bytes()])
+    )
+}
+
+/**
+Returns path of cached executable and warnings printed while it was compiled,
+if an executable with given key was built before.
+*/
+pub fn lookup(key: &str) -> Option<(String, String)> {
+    let binary = binary_path(key);
+    if !Path::new(&binary).is_file() {
+        return None;
+    }
+
+    let warnings = fs::read_to_string(warnings_path(key)).unwrap_or_default();
+    Some((binary, warnings))
+}
+
+/**
+Moves freshly compiled executable into the cache and returns its new path.
+Executable is renamed into place, so concurrent tests never see a partially written file.
+Executable stored before for the same owner (test driver and program) is removed.
+*/
+pub fn store(key: &str, owner: &str, compiled: &str, warnings: &str) -> io::Result<String> {
+    fs::create_dir_all(CACHE_DIR)?;
+    if let Ok(previous) = fs::read_to_string(latest_path(owner)) {
+        if previous != key {
+            let _ = fs::remove_file(binary_path(&previous));
+            let _ = fs::remove_file(warnings_path(&previous));
+        }
+    }
+    fs::write(warnings_path(key), warnings)?;
+
+    let binary = binary_path(key);
+    if fs::rename(compiled, &binary).is_err() {
+        // Rename does not work between filesystems, copy through temporary file instead.
+        let temporary = format!("{}.{}", binary, compiled.replace('/', "_"));
+        fs::copy(compiled, &temporary)?;
+        fs::rename(&temporary, &binary)?;
+        fs::remove_file(compiled)?;
+    }
+    fs::write(latest_path(owner), key)?;
+    Ok(binary)
+}
+
+/// Returns true when given path points to an executable owned by the cache
+pub fn is_cached(path: &str) -> bool {
+    Path::new(path).starts_with(CACHE_DIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_chunks_test() {
+        assert_eq!(hash_chunks(&[b"ab", b"c"]), hash_chunks(&[b"ab", b"c"]));
+        assert_ne!(hash_chunks(&[b"ab", b"c"]), hash_chunks(&[b"a", b"bc"]));
+        assert_ne!(hash_chunks(&[b"-O2"]), hash_chunks(&[b"-O3"]));
+    }
+
+    #[test]
+    fn headers_test() {
+        let directory = std::env::temp_dir().join(format!("rtest_headers{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let files = [
+            (
+                "t.c",
+                "#include <stdio.h>\n# include \"a.h\"\n#include \"missing.h\"\n",
+            ),
+            ("a.h", "#include \"b.h\"\n"),
+            ("b.h", "#include \"a.h\"\nint x;\n"),
+        ];
+        for (file, content) in files {
+            fs::write(directory.join(file), content).unwrap();
+        }
+
+        let driver = directory.join("t.c").to_string_lossy().to_string();
+        let mut headers = BTreeSet::new();
+        collect_headers(Path::new(&driver), &mut headers);
+        let before = cache_key(&[&driver], &[]).unwrap();
+        fs::write(directory.join("b.h"), "int y;\n").unwrap();
+        let after = cache_key(&[&driver], &[]).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            headers.into_iter().collect::<Vec<_>>(),
+            [directory.join("a.h"), directory.join("b.h")]
+        );
+        assert_ne!(before, after);
+    }
+}
@@ -0,0 +1,145 @@
+//! Optional build step run before testing (e.g. make target or CMake build directory)
+//! and detection of executables which are older than their sources.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::settings::Options;
+
+/// Extensions of files which are treated as sources of tested program
+const SOURCE_EXTENSIONS: [&str; 7] = ["c", "h", "cc", "cpp", "hpp", "rs", "s"];
+
+/// Possible outcomes of build step
+#[derive(Debug)]
+pub enum BuildOutcome {
+    Skipped,
+    Built,
+    Failed(String),
+}
+
+/**
+Runs build command from settings through shell. Output of the command is shown
+directly in the console.
+*/
+pub fn run_build_command(settings: &Options) -> BuildOutcome {
+    let command = settings.get_build_command().trim();
+    if command.is_empty() {
+        return BuildOutcome::Skipped;
+    }
+
+    match Command::new("sh").arg("-c").arg(command).status() {
+        Err(e) => BuildOutcome::Failed(e.to_string()),
+        Ok(status) if status.success() => BuildOutcome::Built,
+        Ok(status) => match status.code() {
+            Some(code) => BuildOutcome::Failed(format!("exitcode {}", code)),
+            None => BuildOutcome::Failed("terminated by signal".to_string()),
+        },
+    }
+}
+
+/**
+Returns source files (from sources directory in settings) which were modified
+after the program executable. Empty sources directory disables the check.
+*/
+pub fn newer_sources(settings: &Options) -> Vec<String> {
+    let sources = settings.get_sources_path();
+    if sources.is_empty() {
+        return Vec::new();
+    }
+
+    let executable = match fs::metadata(settings.get_program_path()).and_then(|m| m.modified()) {
+        Ok(time) => time,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut result = Vec::new();
+    collect_newer(Path::new(sources), executable, &mut result);
+    result.sort();
+    result
+}
+
+fn collect_newer(directory: &Path, executable: SystemTime, result: &mut Vec<String>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+        if path.is_dir() {
+            if !hidden {
+                collect_newer(&path, executable, result);
+            }
+            continue;
+        }
+
+        let is_source = path
+            .extension()
+            .map(|extension| SOURCE_EXTENSIONS.iter().any(|e| extension == *e))
+            .unwrap_or(false);
+        let modified = entry.metadata().and_then(|m| m.modified());
+
+        if let (true, Ok(modified)) = (is_source, modified) {
+            if modified > executable {
+                result.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn collect_newer_test() {
+        let directory = std::env::temp_dir().join(format!("rtest_build{}", std::process::id()));
+        fs::create_dir_all(directory.join("nested")).unwrap();
+        fs::create_dir_all(directory.join(".git")).unwrap();
+        for file in ["old.c", "nested/new.h", "notes.txt", ".git/hidden.c"] {
+            fs::write(directory.join(file), "").unwrap();
+        }
+
+        let old = fs::metadata(directory.join("old.c"))
+            .and_then(|m| m.modified())
+            .unwrap();
+        let executable = old + Duration::from_secs(60);
+        let newer = executable + Duration::from_secs(60);
+        for file in ["nested/new.h", "notes.txt", ".git/hidden.c"] {
+            fs::File::options()
+                .write(true)
+                .open(directory.join(file))
+                .and_then(|f| f.set_modified(newer))
+                .unwrap();
+        }
+
+        let mut result = Vec::new();
+        collect_newer(&directory, executable, &mut result);
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            result,
+            vec![directory.join("nested/new.h").to_string_lossy().to_string()]
+        );
+    }
+
+    #[test]
+    fn build_command_test() {
+        let mut settings = Options::default();
+        assert!(matches!(
+            run_build_command(&settings),
+            BuildOutcome::Skipped
+        ));
+        settings.set_build_command("true");
+        assert!(matches!(run_build_command(&settings), BuildOutcome::Built));
+        settings.set_build_command("exit 3");
+        assert!(
+            matches!(run_build_command(&settings), BuildOutcome::Failed(e) if e == "exitcode 3")
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Cancellation of a run: Ctrl+C handling and fail-fast stopping. Running children and
+//! temporary files are registered here, so children can be killed and files created by
+//! this process removed when the run is interrupted.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// True while tests are run, Ctrl+C then cancels the run instead of exiting
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// True when run was interrupted with Ctrl+C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+/// True when no new tests should be started (interrupted or failed with fail-fast)
+static STOPPED: AtomicBool = AtomicBool::new(false);
+/// Process ids of running children
+static CHILDREN: Mutex<Option<HashSet<u32>>> = Mutex::new(None);
+/// Paths of temporary files created by this process, which were not removed yet
+static TEMP_FILES: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/**
+Installs Ctrl+C handler. During a run it kills all running children and stops scheduling
+of new tests, otherwise it removes temporary files and exits the program.
+*/
+pub fn install_handler() {
+    let installed = ctrlc::set_handler(|| {
+        if ACTIVE.load(Ordering::SeqCst) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            STOPPED.store(true, Ordering::SeqCst);
+            kill_children();
+        } else {
+            remove_temp_files();
+            std::process::exit(130);
+        }
+    });
+
+    if let Err(e) = installed {
+        eprintln!("ERROR: Installing Ctrl+C handler FAILED. ({})", e);
+    }
+}
+
+fn kill_children() {
+    let children = CHILDREN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for pid in children.iter().flatten() {
+        // SAFETY: kill only sends a signal. Registered children are unregistered (under this
+        // lock) before they are reaped, so pid still belongs to a running child or a zombie.
+        unsafe {
+            libc::kill(*pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+/// Registers a running child, so it is killed when the run is interrupted
+pub fn register(pid: u32) {
+    let mut children = CHILDREN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    children.get_or_insert_with(HashSet::new).insert(pid);
+}
+
+/**
+Waits until given child exits and unregisters it, without reaping it. Exited child stays
+a zombie, so its pid can not be given to another process before it is unregistered. Child
+has to be reaped afterwards (e.g. with wait4). Returns false when waiting does not block
+and the child is still running.
+*/
+pub fn wait_exit(pid: u32, block: bool) -> io::Result<bool> {
+    let options = match block {
+        true => libc::WEXITED | libc::WNOWAIT,
+        false => libc::WEXITED | libc::WNOWAIT | libc::WNOHANG,
+    };
+    // SAFETY: siginfo_t is a plain C structure, for which all zero bytes are a valid value.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: info points to valid memory, WNOWAIT leaves the child waitable.
+        let result = unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, options) };
+        if result == 0 {
+            break;
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            unregister(pid);
+            return Err(error);
+        }
+    }
+
+    // SAFETY: waitid succeeded, so info was filled (pid is 0 while the child is running).
+    if unsafe { info.si_pid() } == 0 {
+        return Ok(false);
+    }
+    unregister(pid);
+    Ok(true)
+}
+
+/// Unregisters a child, which has finished
+pub fn unregister(pid: u32) {
+    let mut children = CHILDREN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(children) = children.as_mut() {
+        children.remove(&pid);
+    }
+}
+
+/// Registers a temporary file created by this process, so it is removed on Ctrl+C
+pub fn track(path: &str) {
+    let mut files = TEMP_FILES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    files
+        .get_or_insert_with(HashSet::new)
+        .insert(path.to_string());
+}
+
+/// Writes a temporary file, registered so it is removed on Ctrl+C
+pub fn write_temp(path: &str, content: &[u8]) -> io::Result<()> {
+    fs::write(path, content)?;
+    track(path);
+    Ok(())
+}
+
+/// Unregisters a temporary file, which was moved or removed
+pub fn untrack(path: &str) {
+    let mut files = TEMP_FILES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(files) = files.as_mut() {
+        files.remove(path);
+    }
+}
+
+/// Removes a temporary file (if it exists) and unregisters it
+pub fn remove_temp(path: &str) {
+    let _ = fs::remove_file(path);
+    untrack(path);
+}
+
+/// Guard of a run, while it exists Ctrl+C cancels the run
+pub struct Run;
+
+impl Run {
+    /// Starts a new run, clearing state of the previous one
+    pub fn start() -> Run {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        STOPPED.store(false, Ordering::SeqCst);
+        ACTIVE.store(true, Ordering::SeqCst);
+        Run
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::SeqCst);
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            remove_temp_files();
+        }
+    }
+}
+
+/// Stops scheduling of new tests (used by fail-fast)
+pub fn stop() {
+    STOPPED.store(true, Ordering::SeqCst);
+}
+
+/// Returns true when no new tests should be started
+pub fn is_stopped() -> bool {
+    STOPPED.load(Ordering::SeqCst)
+}
+
+/// Returns true when the last run was interrupted with Ctrl+C
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Returns error when the current run was interrupted with Ctrl+C
+pub fn check_interrupted() -> io::Result<()> {
+    if is_interrupted() {
+        Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "interrupted with Ctrl+C",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Removes temporary files created by this process (rtest_stdout*, rtest_compilation*...)
+fn remove_temp_files() {
+    let files = TEMP_FILES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take();
+    for file in files.into_iter().flatten() {
+        let _ = fs::remove_file(file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_files_test() {
+        let directory = std::env::temp_dir().join(format!("rtest_cancel{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = |name: &str| directory.join(name).to_string_lossy().to_string();
+        for name in ["tracked", "removed", "foreign"] {
+            fs::write(path(name), "").unwrap();
+        }
+
+        track(&path("tracked"));
+        track(&path("removed"));
+        remove_temp(&path("removed"));
+        remove_temp_files();
+
+        let mut left: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        left.sort();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(left, ["foreign"]);
+    }
+
+    #[test]
+    fn wait_exit_test() {
+        let registered = |pid: u32| {
+            CHILDREN
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|children| children.contains(&pid))
+        };
+        let mut child = std::process::Command::new("sleep")
+            .arg("0.2")
+            .spawn()
+            .unwrap();
+        register(child.id());
+
+        assert!(!wait_exit(child.id(), false).unwrap());
+        assert!(registered(child.id()));
+        assert!(wait_exit(child.id(), true).unwrap());
+        assert!(!registered(child.id()));
+        // Exited child was not reaped, so its status is still available.
+        assert!(child.wait().unwrap().success());
+    }
+}
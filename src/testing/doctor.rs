@@ -0,0 +1,341 @@
+//! Diagnostics of configuration and environment: tested program, test folder and tools
+//! (diff, valgrind, compilers) with their versions. The same checks validate settings
+//! before testing, so misconfiguration is reported instead of panicking during a run.
+
+use std::collections::BTreeSet;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+use super::{Pipeline, TestResult};
+use crate::settings::Options;
+
+/// Lowest supported versions of tools (major, minor)
+const MIN_DIFF_VERSION: (u32, u32) = (3, 0);
+const MIN_VALGRIND_VERSION: (u32, u32) = (3, 10);
+const MIN_GCC_VERSION: (u32, u32) = (5, 0);
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warning,
+    /// Testing can not be conducted
+    Error,
+}
+
+/// Single finding of diagnostics
+#[derive(Debug, Clone)]
+pub struct Diagnosis {
+    pub severity: Severity,
+    /// Key of localized message
+    pub message: &'static str,
+    /// Path, tool version or list of files the message is about
+    pub detail: String,
+}
+
+impl Diagnosis {
+    fn new(severity: Severity, message: &'static str, detail: impl Into<String>) -> Diagnosis {
+        Diagnosis {
+            severity,
+            message,
+            detail: detail.into(),
+        }
+    }
+}
+
+/**
+Returns (major, minor) version found in output of `tool --version`, which is the first
+number with a dot not being part of a word (e.g. "valgrind-3.22.0" or "gcc (Debian) 12.2.0").
+*/
+fn parse_version(text: &str) -> Option<(u32, u32)> {
+    let chars: Vec<char> = text.chars().collect();
+    for start in 0..chars.len() {
+        let preceded_by_word = start > 0 && chars[start - 1].is_alphanumeric();
+        if !chars[start].is_ascii_digit() || preceded_by_word {
+            continue;
+        }
+
+        let number: String = chars[start..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit() || **c == '.')
+            .collect();
+        let mut parts = number.split('.');
+        let major = parts.next().and_then(|part| part.parse().ok());
+        let minor = parts.next().and_then(|part| part.parse().ok());
+        if let (Some(major), Some(minor)) = (major, minor) {
+            return Some((major, minor));
+        }
+    }
+    None
+}
+
+/// Checks that a tool is available, with at least given version when it is set
+fn check_tool(tool: &str, minimum: Option<(u32, u32)>) -> Diagnosis {
+    let output = match Command::new(tool).arg("--version").output() {
+        Ok(output) => output,
+        Err(_) => return Diagnosis::new(Severity::Error, "DOCTOR_TOOL_MISSING", tool),
+    };
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    let first_line = text.lines().next().unwrap_or(tool).trim().to_string();
+
+    match (minimum, parse_version(&text)) {
+        (Some(minimum), Some(version)) if version < minimum => Diagnosis::new(
+            Severity::Error,
+            "DOCTOR_TOOL_OLD",
+            format!("{} (>= {}.{})", first_line, minimum.0, minimum.1),
+        ),
+        (Some(_), None) => Diagnosis::new(Severity::Warning, "DOCTOR_TOOL_UNKNOWN", first_line),
+        _ => Diagnosis::new(Severity::Ok, "DOCTOR_TOOL_OK", first_line),
+    }
+}
+
+/// Checks that tested program (executable, .o file or interpreted program) can be used
+fn check_program(settings: &Options) -> Vec<Diagnosis> {
+    let path = settings.get_program_path();
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => {
+            return vec![Diagnosis::new(
+                Severity::Error,
+                "DOCTOR_PROGRAM_MISSING",
+                path,
+            )]
+        }
+    };
+
+    let mut result = Vec::new();
+    if settings.is_interpreted() {
+        let interpreter = settings
+            .get_program_invocation()
+            .ok()
+            .and_then(|command| command.into_iter().next())
+            .unwrap_or_default();
+        if which(&interpreter) {
+            result.push(Diagnosis::new(
+                Severity::Ok,
+                "DOCTOR_INTERPRETER_OK",
+                interpreter,
+            ));
+        } else {
+            result.push(Diagnosis::new(
+                Severity::Error,
+                "DOCTOR_INTERPRETER_MISSING",
+                interpreter,
+            ));
+        }
+    } else if !settings.get_program_mode() && metadata.permissions().mode() & 0o111 == 0 {
+        // In compiled mode the program is an object file linked with tests, not run directly.
+        result.push(Diagnosis::new(
+            Severity::Error,
+            "DOCTOR_PROGRAM_NOT_EXECUTABLE",
+            path,
+        ));
+        return result;
+    }
+
+    result.insert(0, Diagnosis::new(Severity::Ok, "DOCTOR_PROGRAM_OK", path));
+    result
+}
+
+/// Returns true when command is a path to a file or can be found in PATH
+fn which(command: &str) -> bool {
+    if command.contains('/') {
+        return Path::new(command).is_file();
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|path| path.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+/// Returns names of tests, whose given file (from accessor) does not exist
+fn missing_files<F>(tests: &[TestResult], file: F) -> Vec<String>
+where
+    F: Fn(&TestResult) -> String,
+{
+    tests
+        .iter()
+        .filter(|test| !Path::new(&file(test)).is_file())
+        .map(|test| test.get_name())
+        .collect()
+}
+
+/**
+Checks test folder: every test should have .out file, and needs .err file when stderr
+is tested (test without it can not be compared).
+*/
+fn check_tests(settings: &Options) -> Vec<Diagnosis> {
+    let path = settings.get_test_path();
+    if !Path::new(path).is_dir() {
+        return vec![Diagnosis::new(
+            Severity::Error,
+            "DOCTOR_TESTS_MISSING",
+            path,
+        )];
+    }
+
+    let loaded = if settings.get_program_mode() {
+        TestResult::load_sources(settings)
+    } else {
+        TestResult::load(path)
+    };
+    let mut tests = match loaded {
+        Ok(tests) => tests,
+        Err(e) => {
+            return vec![Diagnosis::new(
+                Severity::Error,
+                "DOCTOR_TESTS_MISSING",
+                e.to_string(),
+            )]
+        }
+    };
+    tests.sort();
+    if tests.is_empty() {
+        return vec![Diagnosis::new(Severity::Error, "DOCTOR_TESTS_EMPTY", path)];
+    }
+
+    let mut result = vec![Diagnosis::new(
+        Severity::Ok,
+        "DOCTOR_TESTS_OK",
+        format!("{} ({})", path, tests.len()),
+    )];
+    let without_out = missing_files(&tests, TestResult::get_stdout_file);
+    if !without_out.is_empty() {
+        result.push(Diagnosis::new(
+            Severity::Warning,
+            "DOCTOR_OUT_MISSING",
+            without_out.join(", "),
+        ));
+    }
+    if settings.get_stderr_option() {
+        let without_err = missing_files(&tests, TestResult::get_stderr_file);
+        if !without_err.is_empty() {
+            result.push(Diagnosis::new(
+                Severity::Error,
+                "DOCTOR_ERR_MISSING",
+                without_err.join(", "),
+            ));
+        }
+    }
+    result
+}
+
+/// Checks tools needed with current settings
+fn check_tools(settings: &Options) -> Vec<Diagnosis> {
+    let mut result = vec![check_tool("diff", Some(MIN_DIFF_VERSION))];
+    let valgrind = match settings.get_execution_wrapper().trim() {
+        "" => settings.get_valgrind_activity() && !settings.is_interpreted(),
+        wrapper => wrapper == "valgrind",
+    };
+    if valgrind {
+        result.push(check_tool("valgrind", Some(MIN_VALGRIND_VERSION)));
+    }
+
+    let empty: Vec<&str> = settings
+        .get_toolchains()
+        .iter()
+        .filter(|(_, command)| command.is_empty())
+        .map(|(extension, _)| extension.as_str())
+        .collect();
+    if !empty.is_empty() {
+        result.push(Diagnosis::new(
+            Severity::Error,
+            "DOCTOR_TOOLCHAIN_EMPTY",
+            empty.join(", "),
+        ));
+    }
+
+    if settings.get_program_mode() && Path::new(settings.get_test_path()).is_dir() {
+        let compilers: BTreeSet<String> = TestResult::load_sources(settings)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|test| Path::new(test.get_test_path()).extension())
+            .filter_map(|extension| settings.get_toolchain(&extension.to_string_lossy()))
+            .filter_map(|toolchain| toolchain.first().cloned())
+            .collect();
+        for compiler in compilers {
+            let minimum = matches!(compiler.as_str(), "gcc" | "g++").then_some(MIN_GCC_VERSION);
+            result.push(check_tool(&compiler, minimum));
+        }
+    }
+    result
+}
+
+/// Checks that limits set in settings can be applied with chosen execution wrapper
+fn check_limits(settings: &Options) -> Vec<Diagnosis> {
+    let mut result = Vec::new();
+    if Pipeline::new(settings).measured() {
+        return result;
+    }
+
+    let factor = settings.get_time_limit_factor();
+    if factor > 0.0 && !settings.get_program_mode() {
+        result.push(Diagnosis::new(
+            Severity::Warning,
+            "DOCTOR_TIME_LIMIT_INACTIVE",
+            factor.to_string(),
+        ));
+    }
+
+    let memory_limit = settings.get_memory_limit();
+    if memory_limit > 0 {
+        result.push(Diagnosis::new(
+            Severity::Warning,
+            "DOCTOR_MEMORY_LIMIT_INACTIVE",
+            format!("{} kB", memory_limit),
+        ));
+    }
+    result
+}
+
+/// Runs all diagnostics of tested program, test folder, tools and limits
+pub fn diagnose(settings: &Options) -> Vec<Diagnosis> {
+    let mut result = check_program(settings);
+    result.extend(check_tests(settings));
+    result.extend(check_tools(settings));
+    result.extend(check_limits(settings));
+    result
+}
+
+/// Returns only problems (warnings and errors) found by diagnostics
+pub fn validate(settings: &Options) -> Vec<Diagnosis> {
+    diagnose(settings)
+        .into_iter()
+        .filter(|diagnosis| diagnosis.severity != Severity::Ok)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_test() {
+        assert_eq!(parse_version("valgrind-3.22.0"), Some((3, 22)));
+        assert_eq!(
+            parse_version("gcc (Debian 12.2.0-14+deb12u1) 12.2.0"),
+            Some((12, 2))
+        );
+        assert_eq!(parse_version("diff (GNU diffutils) 3.8"), Some((3, 8)));
+        assert_eq!(parse_version("x86_64 tool 2"), None);
+    }
+
+    #[test]
+    fn check_limits_test() {
+        let mut settings = Options::default();
+        settings.set_memory_limit(1024);
+        settings.set_time_limit_factor(3.0);
+        settings.set_valgrind_activity(true);
+        let messages: Vec<&str> = check_limits(&settings)
+            .iter()
+            .map(|diagnosis| diagnosis.message)
+            .collect();
+        assert_eq!(
+            messages,
+            ["DOCTOR_TIME_LIMIT_INACTIVE", "DOCTOR_MEMORY_LIMIT_INACTIVE"]
+        );
+
+        settings.set_valgrind_activity(false);
+        assert!(check_limits(&settings).is_empty());
+    }
+}
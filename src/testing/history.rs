@@ -0,0 +1,109 @@
+//! History of test runs kept between runs of the program in .rtest_history.json file.
+//! For every test it holds outcomes of its latest attempts, so flip rate of flaky tests
+//! can be tracked, and time of its last run, used to schedule slow tests first.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use super::TestResult;
+
+/// File (relative to working directory) where history is stored
+pub const HISTORY_FILE: &str = ".rtest_history.json";
+
+/// Number of latest attempts of a test kept in history
+const KEPT_ATTEMPTS: usize = 50;
+
+/// Past runs of a single test
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TestHistory {
+    /// Outcomes of the latest attempts (true - passed), oldest first
+    pub attempts: Vec<bool>,
+    /// Time of the last run of the test, in seconds
+    pub time: Option<f32>,
+}
+
+impl TestHistory {
+    /**
+    Returns part of consecutive attempts with different outcomes,
+    None when there are less than two attempts.
+    */
+    pub fn flip_rate(&self) -> Option<f32> {
+        if self.attempts.len() < 2 {
+            return None;
+        }
+
+        let flips = self
+            .attempts
+            .windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .count();
+        Some(flips as f32 / (self.attempts.len() - 1) as f32)
+    }
+}
+
+/// History of all tests, by absolute path of test
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct History {
+    tests: BTreeMap<String, TestHistory>,
+}
+
+impl History {
+    /// Loads history from history file, missing or broken file gives empty history
+    pub fn load() -> History {
+        fs::read_to_string(HISTORY_FILE)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves history to history file
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(HISTORY_FILE, content)
+    }
+
+    /// Returns history of a test with given path, if it was run before
+    pub fn get(&self, test_path: &str) -> Option<&TestHistory> {
+        self.tests.get(test_path)
+    }
+
+    /**
+    Adds attempts of given result to history. Every attempt but the last one failed,
+    the last one failed or passed together with the test.
+    */
+    pub fn record(&mut self, result: &TestResult) {
+        let test = self
+            .tests
+            .entry(result.get_test_path().to_string())
+            .or_default();
+
+        for _ in 1..result.get_attempts() {
+            test.attempts.push(false);
+        }
+        test.attempts.push(result.passed());
+        if result.get_time() > 0.0 {
+            test.time = Some(result.get_time());
+        }
+
+        let excess = test.attempts.len().saturating_sub(KEPT_ATTEMPTS);
+        test.attempts.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flip_rate_test() {
+        let history = TestHistory {
+            attempts: vec![true, true, false, true, true],
+            time: None,
+        };
+
+        assert_eq!(history.flip_rate(), Some(0.5));
+        assert_eq!(TestHistory::default().flip_rate(), None);
+    }
+}
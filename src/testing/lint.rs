@@ -0,0 +1,250 @@
+//! Linting of test folder: missing and orphan files of tests, empty expected outputs,
+//! CRLF line endings, trailing whitespace, non-UTF-8 content and duplicate inputs.
+//! Line endings of expected outputs can be fixed automatically. Inputs and trailing
+//! whitespace are never changed, as the tested program could see the difference.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::settings::Options;
+
+/// Extensions of files holding expected results of a test
+const EXPECTED_EXTENSIONS: [&str; 3] = ["out", "err", "code"];
+
+/// Problem found in test folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// Test has no .out file
+    MissingOut,
+    /// Stderr is tested, but test has no .err file
+    MissingErr,
+    /// Expected file (.out, .err, .code) without a test
+    Orphan,
+    /// Expected output is empty
+    EmptyExpected,
+    /// File has Windows (CRLF) line endings
+    CrLf,
+    /// Lines of expected output end with spaces or tabs
+    TrailingWhitespace,
+    /// File is not a valid UTF-8 text
+    NotUtf8,
+    /// Input is the same as input of another test (given name)
+    DuplicateInput(String),
+}
+
+impl Issue {
+    /// Returns key of localized description of issue
+    pub fn literal(&self) -> &'static str {
+        match self {
+            Issue::MissingOut => "LINT_MISSING_OUT",
+            Issue::MissingErr => "LINT_MISSING_ERR",
+            Issue::Orphan => "LINT_ORPHAN",
+            Issue::EmptyExpected => "LINT_EMPTY_EXPECTED",
+            Issue::CrLf => "LINT_CRLF",
+            Issue::TrailingWhitespace => "LINT_TRAILING_WHITESPACE",
+            Issue::NotUtf8 => "LINT_NOT_UTF8",
+            Issue::DuplicateInput(_) => "LINT_DUPLICATE_INPUT",
+        }
+    }
+}
+
+/// Issue found in a file of test folder
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub path: PathBuf,
+    pub issue: Issue,
+}
+
+impl Finding {
+    /// Returns name of the file
+    pub fn get_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /**
+    Returns true when finding can be fixed without changing meaning of the test, which are
+    CRLF line endings of expected outputs (diff would report them as a difference).
+    */
+    pub fn fixable(&self) -> bool {
+        let expected = self
+            .path
+            .extension()
+            .is_some_and(|extension| EXPECTED_EXTENSIONS.iter().any(|e| extension == *e));
+        self.issue == Issue::CrLf && expected
+    }
+}
+
+/// Returns name of file up to the first dot, which is common to all files of a test
+fn core_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name.split('.').next().unwrap_or_default().to_string()
+}
+
+/// Returns true when any line of text ends with a space or a tab
+fn has_trailing_whitespace(text: &str) -> bool {
+    text.lines()
+        .any(|line| line.ends_with(' ') || line.ends_with('\t'))
+}
+
+/// Checks content of a single file, expected outputs are checked for whitespace too
+fn check_content(path: &Path, expected: bool, findings: &mut Vec<Finding>) {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+    let finding = |issue| Finding {
+        path: path.to_path_buf(),
+        issue,
+    };
+
+    if expected && content.is_empty() {
+        findings.push(finding(Issue::EmptyExpected));
+    }
+    let text = match std::str::from_utf8(&content) {
+        Ok(text) => text,
+        Err(_) => {
+            findings.push(finding(Issue::NotUtf8));
+            return;
+        }
+    };
+    if text.contains("\r\n") {
+        findings.push(finding(Issue::CrLf));
+    }
+    if expected && has_trailing_whitespace(&text.replace("\r\n", "\n")) {
+        findings.push(finding(Issue::TrailingWhitespace));
+    }
+}
+
+/// Returns true when file is a test (input file or test driver in compiled mode)
+fn is_test(path: &Path, settings: &Options) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if settings.get_program_mode() => {
+            settings.get_toolchain(extension).is_some()
+        }
+        Some(extension) => extension == "in",
+        None => false,
+    }
+}
+
+/// Lints test folder from settings, findings are sorted by file
+pub fn lint(settings: &Options) -> io::Result<Vec<Finding>> {
+    let mut tests = BTreeMap::new();
+    let mut expected: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for entry in fs::read_dir(settings.get_test_path())? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if is_test(&path, settings) {
+            tests.insert(core_name(&path), path);
+        } else if EXPECTED_EXTENSIONS.contains(&extension.as_str()) {
+            expected.entry(core_name(&path)).or_default().push(path);
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut inputs: BTreeMap<Vec<u8>, String> = BTreeMap::new();
+    for (core, test) in &tests {
+        let files = expected.get(core).cloned().unwrap_or_default();
+        let has = |extension: &str| {
+            files
+                .iter()
+                .any(|file| file.extension().is_some_and(|found| found == extension))
+        };
+        if !has("out") {
+            findings.push(Finding {
+                path: test.clone(),
+                issue: Issue::MissingOut,
+            });
+        }
+        if settings.get_stderr_option() && !has("err") {
+            findings.push(Finding {
+                path: test.clone(),
+                issue: Issue::MissingErr,
+            });
+        }
+
+        check_content(test, false, &mut findings);
+        if let Ok(content) = fs::read(test) {
+            let name = test.file_name().unwrap().to_string_lossy().to_string();
+            match inputs.get(&content) {
+                Some(first) => findings.push(Finding {
+                    path: test.clone(),
+                    issue: Issue::DuplicateInput(first.clone()),
+                }),
+                None => {
+                    inputs.insert(content, name);
+                }
+            }
+        }
+    }
+
+    for (core, files) in &expected {
+        for file in files {
+            if !tests.contains_key(core) {
+                findings.push(Finding {
+                    path: file.clone(),
+                    issue: Issue::Orphan,
+                });
+            }
+            // Exit code files hold a number, whitespace is not compared there.
+            let output = file
+                .extension()
+                .is_none_or(|extension| extension != "code");
+            check_content(file, output, &mut findings);
+        }
+    }
+
+    findings.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(findings)
+}
+
+/**
+Fixes all fixable findings: converts line endings of expected outputs to LF.
+Returns number of fixed files.
+*/
+pub fn fix(findings: &[Finding]) -> io::Result<usize> {
+    let files: BTreeSet<&PathBuf> = findings
+        .iter()
+        .filter(|finding| finding.fixable())
+        .map(|finding| &finding.path)
+        .collect();
+
+    for file in &files {
+        let text = fs::read_to_string(file)?;
+        fs::write(file, text.replace("\r\n", "\n"))?;
+    }
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixable_test() {
+        let finding = |path: &str, issue| Finding {
+            path: PathBuf::from(path),
+            issue,
+        };
+        assert!(finding("t.out", Issue::CrLf).fixable());
+        assert!(finding("t.code", Issue::CrLf).fixable());
+        assert!(!finding("t.in", Issue::CrLf).fixable());
+        assert!(!finding("t.out", Issue::TrailingWhitespace).fixable());
+        assert!(has_trailing_whitespace("a\nb \n"));
+        assert!(!has_trailing_whitespace("a\nb\n"));
+    }
+}
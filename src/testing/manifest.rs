@@ -0,0 +1,171 @@
+//! Manifest of a test suite, rtest.manifest file placed in the test folder.
+//! Every line describes one test (by file name, with or without extension)
+//! or the whole suite, EXAMPLE:
+//!
+//! ```text
+//! # comment
+//! suite points=40
+//! basic.in points=2
+//! leaks points=5 valgrind-required
+//! timing exclusive
+//! ```
+//!
+//! Points can be also given in a `points` file in the test folder, one test name and its
+//! points per line (e.g. `basic.in 2`, `suite 40`), they override points from the manifest.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Name of manifest file in the test folder
+pub const MANIFEST_FILE: &str = "rtest.manifest";
+/// Name of file with points of tests in the test folder
+pub const POINTS_FILE: &str = "points";
+
+/// Settings of a single test read from manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestEntry {
+    pub points: f32,
+    pub valgrind_required: bool,
+    /// Test has to run alone, when no other test is running
+    pub exclusive: bool,
+}
+
+impl Default for TestEntry {
+    fn default() -> Self {
+        TestEntry {
+            points: 1.0,
+            valgrind_required: false,
+            exclusive: false,
+        }
+    }
+}
+
+/// Parsed manifest of a test suite
+#[derive(Debug, Default)]
+pub struct Manifest {
+    entries: HashMap<String, TestEntry>,
+    suite_points: Option<f32>,
+}
+
+impl Manifest {
+    /**
+    Loads manifest and points file from given test folder, missing files give default
+    settings.
+    */
+    pub fn load(test_path: &str) -> Manifest {
+        let mut manifest = match fs::read_to_string(Path::new(test_path).join(MANIFEST_FILE)) {
+            Ok(content) => Manifest::parse(&content),
+            Err(_) => Manifest::default(),
+        };
+        if let Ok(content) = fs::read_to_string(Path::new(test_path).join(POINTS_FILE)) {
+            manifest.parse_points(&content);
+        }
+        manifest
+    }
+
+    /// Sets points from content of points file, malformed lines are ignored
+    pub fn parse_points(&mut self, content: &str) {
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            let (name, points) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [name, points] => match points.parse() {
+                    Ok(points) => (name, points),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+
+            if name == "suite" {
+                self.suite_points = Some(points);
+            } else {
+                let stem = name.split('.').next().unwrap_or(name);
+                let key = match self.entries.contains_key(name) {
+                    false if self.entries.contains_key(stem) => stem,
+                    _ => name,
+                };
+                self.entries.entry(key.to_string()).or_default().points = points;
+            }
+        }
+    }
+
+    /// Parses content of manifest file, unknown flags and malformed values are ignored
+    pub fn parse(content: &str) -> Manifest {
+        let mut manifest = Manifest::default();
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let name = match words.next() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let mut entry = TestEntry::default();
+            for word in words {
+                match word.split_once('=') {
+                    Some(("points", value)) => {
+                        if let Ok(points) = value.parse() {
+                            entry.points = points;
+                        }
+                    }
+                    None if word == "valgrind-required" => entry.valgrind_required = true,
+                    None if word == "exclusive" => entry.exclusive = true,
+                    _ => {}
+                }
+            }
+
+            if name == "suite" {
+                manifest.suite_points = Some(entry.points);
+            } else {
+                manifest.entries.insert(name.to_string(), entry);
+            }
+        }
+        manifest
+    }
+
+    /// Returns settings of a test with given file name (e.g. abc.in)
+    pub fn entry(&self, name: &str) -> TestEntry {
+        let stem = name.split('.').next().unwrap_or(name);
+        self.entries
+            .get(name)
+            .or_else(|| self.entries.get(stem))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns points of the whole suite, which test points are scaled to (if set)
+    pub fn get_suite_points(&self) -> Option<f32> {
+        self.suite_points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let manifest = Manifest::parse(
+            "# header\nsuite points=40\nbasic.in points=2\nleaks points=5 valgrind-required\ntiming exclusive\n",
+        );
+
+        assert_eq!(manifest.get_suite_points(), Some(40.0));
+        assert_eq!(manifest.entry("basic.in").points, 2.0);
+        assert!(manifest.entry("leaks.in").valgrind_required);
+        assert!(manifest.entry("timing.in").exclusive);
+        assert_eq!(manifest.entry("other.in"), TestEntry::default());
+    }
+
+    #[test]
+    fn points_test() {
+        let mut manifest = Manifest::parse("leaks points=5 valgrind-required\n");
+        manifest.parse_points("suite 20\nleaks.in 3\nbasic.in 2 # comment\nbroken x\n");
+
+        assert_eq!(manifest.get_suite_points(), Some(20.0));
+        assert_eq!(manifest.entry("leaks.in").points, 3.0);
+        assert!(manifest.entry("leaks.in").valgrind_required);
+        assert_eq!(manifest.entry("basic.in").points, 2.0);
+        assert_eq!(manifest.entry("broken.in"), TestEntry::default());
+    }
+}
@@ -0,0 +1,159 @@
+//! Measurement of resources used by a child process (wall time, CPU times, peak memory),
+//! collected with wait4, so only the child itself is measured.
+
+use std::io::{self, Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::cancel;
+
+/// Interval of checking wheter a run with a deadline has finished
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Resources used by a single run of a program
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    /// Wall time from spawning to exit, in seconds
+    pub wall: f64,
+    /// CPU time spent in user mode, in seconds
+    pub user: f64,
+    /// CPU time spent in kernel mode, in seconds
+    pub system: f64,
+    /// Peak resident set size, in kilobytes
+    pub max_rss_kb: u64,
+    /// True when the run was killed after exceeding its deadline
+    pub timed_out: bool,
+}
+
+impl Measurement {
+    /// Returns CPU time (user and system), in seconds
+    pub fn cpu(&self) -> f64 {
+        self.user + self.system
+    }
+}
+
+fn seconds(time: libc::timeval) -> f64 {
+    time.tv_sec as f64 + time.tv_usec as f64 / 1_000_000.0
+}
+
+/// Reads whole pipe of a child in a separate thread
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut content = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut content);
+        }
+        content
+    })
+}
+
+/**
+Runs command (program and its arguments) with given stdin and returns its output
+together with resources used by the child process.
+*/
+pub fn run_measured(command: &[String], input: &[u8]) -> io::Result<(Output, Measurement)> {
+    run_measured_with(command, input, &[], None)
+}
+
+/**
+Runs command like run_measured, with given environment variables set. When a deadline
+is given, the child is killed once its wall time exceeds it.
+*/
+pub fn run_measured_with(
+    command: &[String],
+    input: &[u8],
+    environment: &[(String, String)],
+    deadline: Option<Duration>,
+) -> io::Result<(Output, Measurement)> {
+    let (program, arguments) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "command is empty"))?;
+    let beggining = Instant::now();
+    let mut process = Command::new(program)
+        .args(arguments)
+        .envs(environment.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    cancel::register(process.id());
+
+    let mut stdin = match process.stdin.take() {
+        Some(stdin) => stdin,
+        None => {
+            let _ = process.kill();
+            let _ = cancel::wait_exit(process.id(), true);
+            let _ = process.wait();
+            return Err(io::Error::other("stdin of child was not opened"));
+        }
+    };
+    let input = input.to_vec();
+    // Program may exit without reading whole input, so error of writing is ignored.
+    let writer = thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let stdout = read_pipe(process.stdout.take());
+    let stderr = read_pipe(process.stderr.take());
+
+    let deadline = deadline.map(|deadline| beggining + deadline);
+    let mut timed_out = false;
+    // Child is unregistered before it is reaped, so Ctrl+C never kills a recycled pid.
+    while !cancel::wait_exit(process.id(), deadline.is_none() || timed_out)? {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            let _ = process.kill();
+            timed_out = true;
+        } else {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+    let wall = beggining.elapsed().as_secs_f64();
+
+    let mut status = 0;
+    // SAFETY: rusage is a plain C structure, for which all zero bytes are a valid value.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: pid belongs to an exited child of this process, which was not reaped yet,
+        // status and usage point to valid memory.
+        let pid = unsafe { libc::wait4(process.id() as libc::pid_t, &mut status, 0, &mut usage) };
+        if pid > 0 {
+            break;
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let _ = writer.join();
+    let output = Output {
+        status: ExitStatus::from_raw(status),
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    let measurement = Measurement {
+        wall,
+        user: seconds(usage.ru_utime),
+        system: seconds(usage.ru_stime),
+        max_rss_kb: usage.ru_maxrss as u64,
+        timed_out,
+    };
+    Ok((output, measurement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_test() {
+        let command = ["sleep".to_string(), "5".to_string()];
+        let deadline = Some(Duration::from_millis(100));
+        let (output, measurement) = run_measured_with(&command, b"", &[], deadline).unwrap();
+
+        assert!(measurement.timed_out);
+        assert!(measurement.wall < 1.0);
+        assert_eq!(output.status.signal(), Some(libc::SIGKILL));
+    }
+}
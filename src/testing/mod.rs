@@ -1,12 +1,34 @@
+pub mod batch;
+pub mod benchmark;
+pub mod build_cache;
+pub mod build_step;
+pub mod cancel;
+pub mod doctor;
+pub mod history;
+pub mod lint;
+pub mod manifest;
+pub mod measure;
+pub mod pipeline;
+pub mod record;
+pub mod reduce;
+pub mod runner;
+pub mod scheduler;
+pub mod scoring;
+pub mod stress;
 pub mod test_enums;
 pub mod test_result;
+pub mod time_limit;
+pub mod watch;
 
-use indicatif::ParallelProgressIterator;
-use rayon::prelude::*;
 use std::fs::DirEntry;
+use std::io;
+use std::process::Output;
 pub use test_result::TestResult;
 
+use crate::error::Result;
 use crate::settings::Options;
+pub use pipeline::Pipeline;
+pub use runner::Runner;
 
 /**
 Checks wheter a given file is a file with .in extension
@@ -22,87 +44,87 @@ fn is_infile(to_test: &DirEntry) -> bool {
 }
 
 /**
-Checks wheter a given file is a file with .c extension
+Checks wheter a given file is a test driver, which extension has a toolchain in settings
 */
-fn is_cfile(to_test: &DirEntry) -> bool {
+fn is_source_file(to_test: &DirEntry, settings: &Options) -> bool {
     let file_test_path = to_test.path();
     let extension = file_test_path.extension();
 
-    match extension {
-        Some(extension) => extension == "c",
+    match extension.and_then(|extension| extension.to_str()) {
+        Some(extension) => settings.get_toolchain(extension).is_some(),
         None => false,
     }
 }
 
-/// Main function to run tests. Produces a vector of results.
-pub fn run_testing(settings: &Options) -> Vec<TestResult> {
-    if settings.get_program_mode() {
-        run_compiled_version(settings)
-    } else {
-        run_feeded_version(settings)
-    }
+/**
+Runs a command with given input on stdin and returns its output.
+Input is written from another thread, so big outputs can not block the child.
+See measure::run_measured for a run which also returns used resources.
+*/
+pub fn run_with_input(command: &[String], input: &[u8]) -> io::Result<Output> {
+    measure::run_measured(command, input).map(|(output, _)| output)
 }
 
-fn run_compiled_version(settings: &Options) -> Vec<TestResult> {
-    let mut list = TestResult::load_c(settings.get_test_path());
-    list.sort();
-    let length = list.len();
+/**
+Main function to run tests. Produces a vector of results, sorted by test path,
+with attempts added to history of runs. See Runner for details.
+*/
+pub fn run_testing(settings: &Options) -> Result<Vec<TestResult>> {
+    Runner::new(settings).run()
+}
 
-    if settings.get_valgrind_activity() {
-        list.par_iter_mut()
-            .progress_count(length as u64)
-            .enumerate()
-            .for_each(|(index, frame)| {
-                frame.test_compiled_with_valgrind(
-                    settings.get_program_path(),
-                    index,
-                    settings.get_stderr_option(),
-                )
-            });
-    } else {
-        list.par_iter_mut()
-            .progress_count(length as u64)
-            .enumerate()
-            .for_each(|(index, frame)| {
-                frame.test_compiled_no_valgrind(
-                    settings.get_program_path(),
-                    index,
-                    settings.get_stderr_option(),
-                )
-            });
+/**
+Runs a single test again (with retries from settings) and returns its new result.
+History of runs is not changed.
+*/
+pub fn rerun(test: &TestResult, settings: &Options) -> TestResult {
+    let mut frame = TestResult::new(test.get_test_path());
+    let pipeline = Pipeline::new(settings);
+    if let Err(e) = time_limit::set_time_limits([&mut frame], settings, &pipeline) {
+        eprintln!("ERROR: Checking relative time limit FAILED. ({})", e);
     }
 
-    list
+    let run = cancel::Run::start();
+    run_test_with_retries(&mut frame, &pipeline, settings, 0);
+    drop(run);
+    frame
 }
 
-fn run_feeded_version(settings: &Options) -> Vec<TestResult> {
-    let mut list = TestResult::load(settings.get_test_path());
+/// Loads sorted tests of the mode chosen in settings
+fn load_tests(settings: &Options) -> Result<Vec<TestResult>> {
+    let mut list = if settings.get_program_mode() {
+        TestResult::load_sources(settings)?
+    } else {
+        TestResult::load(settings.get_test_path())?
+    };
     list.sort();
-    let length = list.len();
+    Ok(list)
+}
 
-    if settings.get_valgrind_activity() {
-        list.par_iter_mut()
-            .progress_count(length as u64)
-            .enumerate()
-            .for_each(|(index, frame)| {
-                frame.test_with_valgrind(
-                    settings.get_program_path(),
-                    index,
-                    settings.get_stderr_option(),
-                )
-            });
-    } else {
-        list.par_iter_mut()
-            .progress_count(length as u64)
-            .enumerate()
-            .for_each(|(index, frame)| {
-                frame.test_no_valgrind(
-                    settings.get_program_path(),
-                    index,
-                    settings.get_stderr_option(),
-                )
-            });
-    }
+/**
+Conducts a single test and reruns it while it fails, as many times as settings allow.
+*/
+fn run_test_with_retries(
+    frame: &mut TestResult,
+    pipeline: &Pipeline,
+    settings: &Options,
+    index: usize,
+) {
+    run_test(frame, pipeline, settings, index);
+    frame.retry(settings.get_retries(), |attempt| {
+        run_test(attempt, pipeline, settings, index)
+    });
+}
 
-    list
+/**
+Conducts a single test with given pipeline. Index should be an unique number
+to all of the tests conducted at the same time.
+*/
+fn run_test(frame: &mut TestResult, pipeline: &Pipeline, settings: &Options, index: usize) {
+    frame.conduct(pipeline, settings, index);
+
+    // Children of an interrupted run are killed, so their results are not reliable.
+    if !cancel::is_interrupted() {
+        frame.set_finished();
+    }
 }
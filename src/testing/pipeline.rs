@@ -0,0 +1,393 @@
+//! Composable steps of a single test: build step preparing tested program, execution
+//! wrapper running it (plain, valgrind, sanitizer or custom command) and output checker.
+//! Pipeline made of steps chosen in settings can have any of them replaced, e.g. by
+//! a program embedding r_test with its own checker.
+
+use super::test_enums::{DiffResult, TestFail};
+use super::TestResult;
+use crate::settings::Options;
+
+/// Exit code of valgrind when it found errors. Exit codes have 8 bits, so it is below 256.
+const VALGRIND_ERROR_CODE: i32 = 195;
+/// Exit code of tested program when a sanitizer found errors
+const SANITIZER_ERROR_CODE: i32 = 86;
+
+/// Tested program prepared for a test by a build step
+#[derive(Debug, Clone)]
+pub struct Executable {
+    /// Command (program and its arguments) running tested program
+    pub command: Vec<String>,
+    /// File produced for the test, removed after it (unless kept in build cache)
+    pub produced: Option<String>,
+}
+
+/// Files with output of a finished run of tested program
+#[derive(Debug, Clone, Copy)]
+pub struct RunOutput<'a> {
+    pub stdout_file: &'a str,
+    pub stderr_file: &'a str,
+    /// Exit code of tested program, None when the wrapper replaced it reporting errors
+    pub exit_code: Option<i32>,
+}
+
+/// Prepares tested program for a test
+pub trait BuildStep: Send + Sync {
+    /// Returns tested program prepared for the test, or cause of failure of the test
+    fn build(
+        &self,
+        test: &mut TestResult,
+        settings: &Options,
+        index: usize,
+    ) -> Result<Executable, TestFail>;
+}
+
+/// Runs tested program under another program (e.g. valgrind) or in a changed environment
+pub trait Wrapper: Send + Sync {
+    /// Returns command which runs given command of tested program under the wrapper
+    fn command(&self, command: Vec<String>) -> Vec<String>;
+
+    /// Returns environment variables set for the run
+    fn environment(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /**
+    Returns failure for errors reported by the wrapper in a finished run. Output of such
+    run is still checked, which gives partial credit for correct output.
+    */
+    fn errors(&self, _exit_code: i32, _stderr: &str) -> Option<TestFail> {
+        None
+    }
+
+    /// Returns failure of a run terminated by a signal
+    fn terminated(&self) -> TestFail {
+        TestFail::ProgramExitCode()
+    }
+
+    /// Returns false when resources used by the run would describe the wrapper itself
+    fn measured(&self) -> bool {
+        true
+    }
+}
+
+/// Compares output of tested program with the expected one
+pub trait Checker: Send + Sync {
+    /// Returns cause of failure, when output of the run is not the expected one of the test
+    fn check(
+        &self,
+        test: &TestResult,
+        output: &RunOutput,
+        settings: &Options,
+    ) -> Result<(), TestFail>;
+}
+
+/// Runs tested program from settings as it is
+pub struct Prebuilt;
+
+impl BuildStep for Prebuilt {
+    fn build(
+        &self,
+        _: &mut TestResult,
+        settings: &Options,
+        _: usize,
+    ) -> Result<Executable, TestFail> {
+        let command = settings
+            .get_program_invocation()
+            .map_err(|e| TestFail::InnerProblem(e.to_string()))?;
+        Ok(Executable {
+            command,
+            produced: None,
+        })
+    }
+}
+
+/// Compiles test driver with tested .o file, using toolchain matching its extension
+pub struct Compile;
+
+impl BuildStep for Compile {
+    fn build(
+        &self,
+        test: &mut TestResult,
+        settings: &Options,
+        index: usize,
+    ) -> Result<Executable, TestFail> {
+        match test.compile_program(settings, index) {
+            Some(compiled) => Ok(Executable {
+                command: vec![format!("./{}", compiled)],
+                produced: Some(compiled),
+            }),
+            None => Err(test.get_failed_cause().clone()),
+        }
+    }
+}
+
+/// Runs tested program directly
+pub struct Plain;
+
+impl Wrapper for Plain {
+    fn command(&self, command: Vec<String>) -> Vec<String> {
+        command
+    }
+}
+
+/// Runs tested program under valgrind, which checks memory errors and leaks
+pub struct Valgrind;
+
+impl Wrapper for Valgrind {
+    fn command(&self, command: Vec<String>) -> Vec<String> {
+        let mut wrapped: Vec<String> = [
+            "valgrind",
+            "--leak-check=full",
+            "--show-leak-kinds=all",
+            "--errors-for-leak-kinds=all",
+            "-q",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        wrapped.push(format!("--error-exitcode={}", VALGRIND_ERROR_CODE));
+        wrapped.extend(command);
+        wrapped
+    }
+
+    fn errors(&self, exit_code: i32, stderr: &str) -> Option<TestFail> {
+        (exit_code == VALGRIND_ERROR_CODE).then(|| TestFail::Valgrind(stderr.to_string()))
+    }
+
+    fn terminated(&self) -> TestFail {
+        TestFail::ValgrindExitCode()
+    }
+
+    fn measured(&self) -> bool {
+        false
+    }
+}
+
+/**
+Runs tested program built with -fsanitize (address, leak or undefined), so that errors
+found by sanitizers fail the test with their reports.
+*/
+pub struct Sanitizer;
+
+impl Wrapper for Sanitizer {
+    fn command(&self, command: Vec<String>) -> Vec<String> {
+        command
+    }
+
+    fn environment(&self) -> Vec<(String, String)> {
+        let exit_code = format!("exitcode={}", SANITIZER_ERROR_CODE);
+        vec![
+            ("ASAN_OPTIONS".to_string(), exit_code.clone()),
+            ("LSAN_OPTIONS".to_string(), exit_code.clone()),
+            (
+                "UBSAN_OPTIONS".to_string(),
+                format!("halt_on_error=1:print_stacktrace=1:{}", exit_code),
+            ),
+        ]
+    }
+
+    fn errors(&self, exit_code: i32, stderr: &str) -> Option<TestFail> {
+        (exit_code == SANITIZER_ERROR_CODE && stderr.contains("Sanitizer"))
+            .then(|| TestFail::Sanitizer(stderr.to_string()))
+    }
+}
+
+/// Runs tested program with a command put before it (e.g. `timeout 5` or `qemu-arm`)
+pub struct Custom {
+    prefix: Vec<String>,
+}
+
+impl Custom {
+    /// Creates wrapper from command, which is split on whitespace
+    pub fn new(command: &str) -> Custom {
+        Custom {
+            prefix: command.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl Wrapper for Custom {
+    fn command(&self, command: Vec<String>) -> Vec<String> {
+        let mut wrapped = self.prefix.clone();
+        wrapped.extend(command);
+        wrapped
+    }
+}
+
+/**
+Compares stdout with .out file and, when stderr is tested, stderr with .err file using
+diff program. Exit code is compared with .code file, if the test has one and the exit
+code of the program is known.
+*/
+pub struct Diff;
+
+impl Diff {
+    /// Returns failure for difference of actual and expected file, made by given variant
+    fn compare(
+        actual: &str,
+        expected: &str,
+        difference: fn(String) -> DiffResult,
+    ) -> Result<(), TestFail> {
+        match TestResult::diff_files(actual, expected) {
+            DiffResult::Ok => Ok(()),
+            DiffResult::DifferenceNotSpecified(error) => Err(TestFail::Diff(difference(error))),
+            other => Err(TestFail::Diff(other)),
+        }
+    }
+}
+
+impl Checker for Diff {
+    fn check(
+        &self,
+        test: &TestResult,
+        output: &RunOutput,
+        settings: &Options,
+    ) -> Result<(), TestFail> {
+        Diff::compare(
+            output.stdout_file,
+            &test.get_stdout_file(),
+            DiffResult::DifferenceStdout,
+        )?;
+        if settings.get_stderr_option() {
+            Diff::compare(
+                output.stderr_file,
+                &test.get_stderr_file(),
+                DiffResult::DifferenceStderr,
+            )?;
+        }
+
+        match (test.get_expected_exit_code(), output.exit_code) {
+            (Some(expected), Some(actual)) if expected != actual => {
+                Err(TestFail::Diff(DiffResult::DifferenceExitCode(format!(
+                    "Expected exitcode {}, program returned {}.",
+                    expected, actual
+                ))))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Steps conducting a single test
+pub struct Pipeline {
+    build_step: Box<dyn BuildStep>,
+    wrapper: Box<dyn Wrapper>,
+    checker: Box<dyn Checker>,
+}
+
+impl Pipeline {
+    /**
+    Creates pipeline chosen in settings: test drivers are compiled in compiled mode and
+    outputs are compared with diff. Execution wrapper setting can be "sanitizer", "valgrind"
+    or any other command put before tested program. Without it, valgrind is used when
+    it is active, except for interpreted programs, as it would check the interpreter.
+    */
+    pub fn new(settings: &Options) -> Pipeline {
+        let build_step: Box<dyn BuildStep> = if settings.get_program_mode() {
+            Box::new(Compile)
+        } else {
+            Box::new(Prebuilt)
+        };
+        let wrapper: Box<dyn Wrapper> = match settings.get_execution_wrapper().trim() {
+            "" if settings.get_valgrind_activity() && !settings.is_interpreted() => {
+                Box::new(Valgrind)
+            }
+            "" => Box::new(Plain),
+            "valgrind" => Box::new(Valgrind),
+            "sanitizer" => Box::new(Sanitizer),
+            command => Box::new(Custom::new(command)),
+        };
+
+        Pipeline {
+            build_step,
+            wrapper,
+            checker: Box::new(Diff),
+        }
+    }
+
+    /// Replaces build step of the pipeline
+    pub fn with_build_step(mut self, build_step: impl BuildStep + 'static) -> Pipeline {
+        self.build_step = Box::new(build_step);
+        self
+    }
+
+    /// Replaces execution wrapper of the pipeline
+    pub fn with_wrapper(mut self, wrapper: impl Wrapper + 'static) -> Pipeline {
+        self.wrapper = Box::new(wrapper);
+        self
+    }
+
+    /// Replaces output checker of the pipeline
+    pub fn with_checker(mut self, checker: impl Checker + 'static) -> Pipeline {
+        self.checker = Box::new(checker);
+        self
+    }
+
+    /// Returns false when resources used by tested program are not measured (e.g. valgrind)
+    pub fn measured(&self) -> bool {
+        self.wrapper.measured()
+    }
+
+    pub(super) fn get_build_step(&self) -> &dyn BuildStep {
+        self.build_step.as_ref()
+    }
+
+    pub(super) fn get_wrapper(&self) -> &dyn Wrapper {
+        self.wrapper.as_ref()
+    }
+
+    pub(super) fn get_checker(&self) -> &dyn Checker {
+        self.checker.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapper_test() {
+        let command = vec!["./program".to_string(), "-v".to_string()];
+        assert_eq!(Plain.command(command.clone()), command);
+        assert_eq!(
+            Custom::new("timeout  5").command(command.clone()),
+            ["timeout", "5", "./program", "-v"]
+        );
+
+        let valgrind = Valgrind.command(command);
+        assert_eq!(valgrind[0], "valgrind");
+        assert!(valgrind.ends_with(&["./program".to_string(), "-v".to_string()]));
+        assert!(Valgrind.errors(VALGRIND_ERROR_CODE, "").is_some());
+        assert!(Sanitizer
+            .errors(SANITIZER_ERROR_CODE, "ERROR: AddressSanitizer")
+            .is_some());
+        assert!(Sanitizer.errors(SANITIZER_ERROR_CODE, "").is_none());
+    }
+
+    #[test]
+    fn diff_exit_code_test() {
+        let directory = std::env::temp_dir().join(format!("rtest_pipeline{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, content) in [("t.in", ""), ("t.out", "ok\n"), ("t.code", "3\n")] {
+            std::fs::write(directory.join(file), content).unwrap();
+        }
+        let test = TestResult::new(&directory.join("t.in").to_string_lossy());
+        let stdout = directory.join("t.out").to_string_lossy().to_string();
+        let output = |exit_code| RunOutput {
+            stdout_file: &stdout,
+            stderr_file: "",
+            exit_code,
+        };
+
+        let settings = Options::default();
+        let known = Diff.check(&test, &output(Some(VALGRIND_ERROR_CODE)), &settings);
+        let unknown = Diff.check(&test, &output(None), &settings);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(matches!(
+            known,
+            Err(TestFail::Diff(DiffResult::DifferenceExitCode(_)))
+        ));
+        assert!(unknown.is_ok());
+    }
+}
@@ -0,0 +1,95 @@
+//! Recording of expected outputs (.out, .err and .code files) from a reference
+//! implementation and blessing of actual outputs of tested program.
+
+use indicatif::ParallelProgressIterator;
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::process::Output;
+
+use super::{cancel, run_with_input, TestResult};
+use crate::settings::Options;
+
+/**
+Saves output of a program as expected output of a test with given core
+(path without extension). Stderr is saved only when write_stderr is true.
+*/
+fn write_expected(core: &str, output: &Output, write_stderr: bool) -> io::Result<()> {
+    fs::write(format!("{}.out", core), &output.stdout)?;
+    if write_stderr {
+        fs::write(format!("{}.err", core), &output.stderr)?;
+    }
+
+    match output.status.code() {
+        Some(code) => fs::write(format!("{}.code", core), format!("{}\n", code)),
+        None => Err(io::Error::other("program was terminated by a signal")),
+    }
+}
+
+/**
+Runs reference implementation from settings over every .in file in test folder and
+writes expected .out, .err and .code files. Returns names of recorded tests and
+descriptions of tests which could not be recorded. Tests are not recorded after Ctrl+C.
+*/
+pub fn record_expected(settings: &Options) -> (Vec<String>, Vec<String>) {
+    let mut list = match TestResult::load(settings.get_test_path()) {
+        Ok(list) => list,
+        Err(e) => return (Vec::new(), vec![e.to_string()]),
+    };
+    list.sort();
+    let length = list.len();
+    let reference = [settings.get_reference_path().to_string()];
+    let _run = cancel::Run::start();
+
+    let outcomes: Vec<Result<String, String>> = list
+        .par_iter()
+        .progress_count(length as u64)
+        .map(|test| {
+            let recorded = cancel::check_interrupted()
+                .and_then(|_| fs::read(test.get_test_path()))
+                .and_then(|input| run_with_input(&reference, &input))
+                // Output of reference killed by Ctrl+C is not the expected one.
+                .and_then(|output| cancel::check_interrupted().map(|_| output))
+                .and_then(|output| write_expected(&test.get_core(), &output, true));
+
+            match recorded {
+                Ok(()) => Ok(test.get_name()),
+                Err(e) => Err(format!("{}: {}", test.get_name(), e)),
+            }
+        })
+        .collect();
+
+    let mut recorded = Vec::new();
+    let mut failed = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Ok(name) => recorded.push(name),
+            Err(description) => failed.push(description),
+        }
+    }
+    (recorded, failed)
+}
+
+/**
+Accepts actual output of tested program as expected output of given test.
+Program is run again (without valgrind), stderr is saved when stderr tests are on
+or the test already has an .err file.
+*/
+pub fn bless(test: &TestResult, settings: &Options) -> io::Result<()> {
+    if settings.get_program_mode() {
+        return Err(io::Error::other("blessing does not support compiled mode"));
+    }
+
+    let input = fs::read(test.get_test_path())?;
+    let _run = cancel::Run::start();
+    let program = settings
+        .get_program_invocation()
+        .map_err(io::Error::other)?;
+    let output = run_with_input(&program, &input)?;
+    cancel::check_interrupted()?;
+
+    let core = test.get_core();
+    let write_stderr =
+        settings.get_stderr_option() || std::path::Path::new(&format!("{}.err", core)).exists();
+    write_expected(&core, &output, write_stderr)
+}
@@ -0,0 +1,324 @@
+//! Delta debugging reduction of failing test inputs. Input is shrunk by lines, then
+//! by tokens, as long as tested program fails in the same way as with the whole input.
+
+use indicatif::ProgressBar;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::cancel;
+use super::test_enums::{DiffResult, TestFail};
+use super::{run_test, run_with_input, Pipeline, TestResult};
+use crate::settings::Options;
+
+/// Path of reduced input (and its expected outputs) without extension
+const CANDIDATE_CORE: &str = "rtest_reduce";
+
+/// Classification of a failure, which reduced input has to reproduce
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// Program was terminated by a signal (if it is known)
+    Signal(Option<i32>),
+    /// Valgrind reported an error, holds the first line of its report
+    Valgrind(String),
+    DifferenceStdout,
+    DifferenceStderr,
+    DifferenceExitCode,
+}
+
+impl Failure {
+    /// Classifies failure of given result, failures which cannot be reduced give None
+    fn of(result: &TestResult) -> Option<Failure> {
+        Failure::classify(result.get_failed_cause(), result.get_signal())
+    }
+
+    /// Classifies failure with given cause and signal which terminated the program
+    fn classify(cause: &TestFail, signal: Option<i32>) -> Option<Failure> {
+        match cause {
+            TestFail::ProgramExitCode() | TestFail::ValgrindExitCode() => {
+                Some(Failure::Signal(signal))
+            }
+            TestFail::Valgrind(report) => Some(Failure::Valgrind(first_valgrind_error(report))),
+            TestFail::Diff(DiffResult::DifferenceStdout(_)) => Some(Failure::DifferenceStdout),
+            TestFail::Diff(DiffResult::DifferenceStderr(_)) => Some(Failure::DifferenceStderr),
+            TestFail::Diff(DiffResult::DifferenceExitCode(_)) => Some(Failure::DifferenceExitCode),
+            _ => None,
+        }
+    }
+
+    /// Returns wheater expected outputs are needed to reproduce the failure
+    fn needs_expected(&self) -> bool {
+        matches!(
+            self,
+            Failure::DifferenceStdout | Failure::DifferenceStderr | Failure::DifferenceExitCode
+        )
+    }
+}
+
+/// Outcome of reduction of a test input
+#[derive(Debug)]
+pub enum ReduceOutcome {
+    /// Reduced input was saved, holds its path and sizes (in bytes) before and after reduction
+    Reduced {
+        saved_as: String,
+        original: usize,
+        reduced: usize,
+    },
+    /// Test does not fail in a way which can be reduced
+    NotFailing,
+    Failed(String),
+}
+
+/**
+Returns the first error line of valgrind report, with process id and numbers removed,
+so errors of the same kind are equal for different inputs.
+*/
+fn first_valgrind_error(report: &str) -> String {
+    report
+        .lines()
+        .filter_map(|line| line.strip_prefix("=="))
+        .filter_map(|line| line.split_once("==").map(|(_, error)| error.trim()))
+        .find(|error| !error.is_empty())
+        .unwrap_or("")
+        .chars()
+        .filter(|character| !character.is_ascii_digit())
+        .collect()
+}
+
+/// Splits input into lines, every line keeps its line ending
+fn split_lines(input: &[u8]) -> Vec<&[u8]> {
+    input.split_inclusive(|&byte| byte == b'\n').collect()
+}
+
+/**
+Splits input into tokens, every token keeps whitespace following it.
+Whitespace at the beginning of input belongs to the first token.
+*/
+fn split_tokens(input: &[u8]) -> Vec<&[u8]> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_whitespace = false;
+
+    for (position, byte) in input.iter().enumerate() {
+        if byte.is_ascii_whitespace() {
+            in_whitespace = true;
+        } else if in_whitespace {
+            if input[start..position]
+                .iter()
+                .any(|byte| !byte.is_ascii_whitespace())
+            {
+                tokens.push(&input[start..position]);
+                start = position;
+            }
+            in_whitespace = false;
+        }
+    }
+    if start < input.len() {
+        tokens.push(&input[start..]);
+    }
+    tokens
+}
+
+/**
+Delta debugging: returns a 1-minimal subsequence of items (removing any single chunk
+of the final granularity makes reproduces return false), for which reproduces is true.
+Reproduces should be true for the whole items.
+*/
+fn ddmin<T: Clone, F>(mut items: Vec<T>, mut reproduces: F) -> io::Result<Vec<T>>
+where
+    F: FnMut(&[T]) -> io::Result<bool>,
+{
+    let mut granularity = 2;
+
+    while items.len() >= 2 {
+        let chunk = items.len().div_ceil(granularity);
+        let mut reduced = false;
+
+        for start in (0..items.len()).step_by(chunk) {
+            let end = (start + chunk).min(items.len());
+
+            let subset = items[start..end].to_vec();
+            if reproduces(&subset)? {
+                items = subset;
+                granularity = 2;
+                reduced = true;
+                break;
+            }
+
+            let mut complement = items[..start].to_vec();
+            complement.extend_from_slice(&items[end..]);
+            if reproduces(&complement)? {
+                items = complement;
+                granularity = (granularity - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced {
+            if granularity >= items.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(items.len());
+        }
+    }
+    Ok(items)
+}
+
+/// Reruns tested program on candidate inputs and checks wheater they fail in the same way
+struct Reducer<'a> {
+    settings: &'a Options,
+    failure: Failure,
+    write_code: bool,
+    progress: ProgressBar,
+}
+
+impl Reducer<'_> {
+    /**
+    Saves candidate input as a test, with expected outputs from the reference
+    implementation when they are needed, and runs it.
+    */
+    fn reproduces(&self, input: &[u8]) -> io::Result<bool> {
+        let test_path = format!("{}.in", CANDIDATE_CORE);
+        cancel::write_temp(&test_path, input)?;
+
+        if self.failure.needs_expected() {
+            let reference = [self.settings.get_reference_path().to_string()];
+            let expected = run_with_input(&reference, input)?;
+            cancel::check_interrupted()?;
+
+            cancel::write_temp(&format!("{}.out", CANDIDATE_CORE), &expected.stdout)?;
+            if self.settings.get_stderr_option() {
+                cancel::write_temp(&format!("{}.err", CANDIDATE_CORE), &expected.stderr)?;
+            }
+            if self.write_code {
+                match expected.status.code() {
+                    Some(code) => cancel::write_temp(
+                        &format!("{}.code", CANDIDATE_CORE),
+                        format!("{}\n", code).as_bytes(),
+                    )?,
+                    None => return Ok(false),
+                }
+            }
+        }
+
+        let mut frame = TestResult::new(&test_path);
+        run_test(&mut frame, &Pipeline::new(self.settings), self.settings, 0);
+        cancel::check_interrupted()?;
+        self.progress.inc(1);
+
+        Ok(Failure::of(&frame).as_ref() == Some(&self.failure))
+    }
+
+    /// Reduces input by lines, then by tokens of remaining lines
+    fn reduce(&self, input: &[u8]) -> io::Result<Vec<u8>> {
+        let lines = ddmin(split_lines(input), |lines| self.reproduces(&lines.concat()))?;
+        let by_lines = lines.concat();
+
+        let tokens = ddmin(split_tokens(&by_lines), |tokens| {
+            self.reproduces(&tokens.concat())
+        })?;
+        Ok(tokens.concat())
+    }
+}
+
+fn cleanup() {
+    for extension in ["in", "out", "err", "code"] {
+        cancel::remove_temp(&format!("{}.{}", CANDIDATE_CORE, extension));
+    }
+}
+
+/**
+Reduces input of a failing test (path to .in file) with delta debugging. Failures with
+wrong output need the reference implementation to produce expected outputs of reduced inputs.
+Reduced input is saved next to the test, as test path with .min extension added.
+*/
+pub fn reduce_test(settings: &Options, test_path: &str) -> ReduceOutcome {
+    if settings.get_program_mode() {
+        return ReduceOutcome::Failed("reduction does not support compiled mode".to_string());
+    }
+
+    let input = match fs::read(test_path) {
+        Ok(input) => input,
+        Err(e) => return ReduceOutcome::Failed(e.to_string()),
+    };
+
+    let _run = cancel::Run::start();
+    let mut original = TestResult::new(test_path);
+    run_test(&mut original, &Pipeline::new(settings), settings, 0);
+    if let Err(e) = cancel::check_interrupted() {
+        return ReduceOutcome::Failed(e.to_string());
+    }
+
+    let failure = match Failure::of(&original) {
+        Some(failure) => failure,
+        None => return ReduceOutcome::NotFailing,
+    };
+    if failure.needs_expected() && !Path::new(settings.get_reference_path()).is_file() {
+        return ReduceOutcome::Failed(
+            "reference implementation is needed to reduce wrong output".to_string(),
+        );
+    }
+
+    let reducer = Reducer {
+        settings,
+        failure,
+        write_code: Path::new(&format!("{}.code", original.get_core())).exists(),
+        progress: ProgressBar::new_spinner(),
+    };
+    let reduced = reducer.reduce(&input);
+    reducer.progress.finish_and_clear();
+    cleanup();
+
+    let reduced = match reduced {
+        Ok(reduced) => reduced,
+        Err(e) => return ReduceOutcome::Failed(e.to_string()),
+    };
+
+    let saved_as = format!("{}.min", test_path);
+    match fs::write(&saved_as, &reduced) {
+        Ok(()) => ReduceOutcome::Reduced {
+            saved_as,
+            original: input.len(),
+            reduced: reduced.len(),
+        },
+        Err(e) => ReduceOutcome::Failed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ddmin_test() {
+        let items: Vec<u32> = (0..20).collect();
+        let reduced = ddmin(items, |items| Ok(items.contains(&3) && items.contains(&17))).unwrap();
+
+        assert_eq!(reduced, vec![3, 17]);
+    }
+
+    #[test]
+    fn split_tokens_test() {
+        let tokens = split_tokens(b"  12 ab\n\n3\t4");
+
+        assert_eq!(tokens, vec![&b"  12 "[..], b"ab\n\n", b"3\t", b"4"]);
+        assert_eq!(split_lines(b"a b\nc\n"), vec![&b"a b\n"[..], b"c\n"]);
+    }
+
+    #[test]
+    fn classify_test() {
+        assert_eq!(
+            Failure::classify(&TestFail::ValgrindExitCode(), Some(11)),
+            Some(Failure::Signal(Some(11)))
+        );
+        assert_eq!(
+            Failure::classify(&TestFail::ProgramExitCode(), None),
+            Some(Failure::Signal(None))
+        );
+        assert_eq!(
+            Failure::classify(&TestFail::CompilationExitCode, None),
+            None
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Builder of test runs. It is the entry point for programs embedding r_test, the menu
+//! runs tests the same way.
+
+use super::cancel;
+use super::history::History;
+use super::manifest::Manifest;
+use super::{load_tests, run_test_with_retries, scheduler, time_limit, Pipeline, TestResult};
+use crate::error::Result;
+use crate::settings::Options;
+
+/// Run of the test suite described by settings
+pub struct Runner<'a> {
+    settings: &'a Options,
+    failed_first: Vec<String>,
+    history: bool,
+    pipeline: Option<Pipeline>,
+}
+
+impl<'a> Runner<'a> {
+    /// Creates a run of tests from given settings, which keeps history of runs
+    pub fn new(settings: &'a Options) -> Runner<'a> {
+        Runner {
+            settings,
+            failed_first: Vec::new(),
+            history: true,
+            pipeline: None,
+        }
+    }
+
+    /// Tests with given paths (e.g. failed in the previous run) are started before all the others
+    pub fn failed_first(mut self, paths: &[String]) -> Runner<'a> {
+        self.failed_first = paths.to_vec();
+        self
+    }
+
+    /**
+    Sets wheter attempts are added to history of runs in .rtest_history.json (true by default).
+    Without history, results have no flip rates.
+    */
+    pub fn history(mut self, record: bool) -> Runner<'a> {
+        self.history = record;
+        self
+    }
+
+    /// Conducts tests with given pipeline instead of the one chosen in settings
+    pub fn pipeline(mut self, pipeline: Pipeline) -> Runner<'a> {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// Returns tests found in test folder, sorted by path and not run yet
+    pub fn discover(&self) -> Result<Vec<TestResult>> {
+        load_tests(self.settings)
+    }
+
+    /**
+    Runs tests and returns their results, sorted by test path. Tests are scheduled slowest
+    first. When the run is interrupted with Ctrl+C or stopped by fail-fast, only finished
+    tests are returned. Problems of single tests are recorded in their results, error is
+    returned only when tests can not be loaded or pool of threads can not be created.
+    */
+    pub fn run(&self) -> Result<Vec<TestResult>> {
+        let settings = self.settings;
+        let mut list = self.discover()?;
+        let mut history = History::load();
+        let manifest = Manifest::load(settings.get_test_path());
+        let chosen;
+        let pipeline = match &self.pipeline {
+            Some(pipeline) => pipeline,
+            None => {
+                chosen = Pipeline::new(settings);
+                &chosen
+            }
+        };
+
+        if let Err(e) = time_limit::set_time_limits(list.iter_mut(), settings, pipeline) {
+            eprintln!("ERROR: Checking relative time limit FAILED. ({})", e);
+        }
+
+        let run = cancel::Run::start();
+        scheduler::sort_slowest_first(&mut list, &history, |test| test);
+        list.sort_by_key(|test| {
+            !self
+                .failed_first
+                .iter()
+                .any(|path| path == test.get_test_path())
+        });
+        scheduler::schedule(
+            &mut list,
+            settings,
+            |test| manifest.entry(&test.get_name()).exclusive,
+            |frame, index| {
+                run_test_with_retries(frame, pipeline, settings, index);
+                settings.get_fail_fast() && !frame.passed()
+            },
+        )?;
+        drop(run);
+        list.retain(|test| test.finished());
+        list.sort();
+
+        if !self.history {
+            return Ok(list);
+        }
+
+        for result in list.iter_mut() {
+            history.record(result);
+            result.set_flip_rate(
+                history
+                    .get(result.get_test_path())
+                    .and_then(|test| test.flip_rate()),
+            );
+        }
+        if let Err(e) = history.save() {
+            eprintln!("ERROR: Saving history of runs FAILED. ({})", e);
+        }
+
+        Ok(list)
+    }
+}
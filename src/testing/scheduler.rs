@@ -0,0 +1,140 @@
+//! Scheduling of tests on a pool of threads, with number of jobs from settings.
+//! Tests are started slowest first (according to history of runs) and tests marked
+//! as exclusive in manifest are run alone, after all the others.
+
+use indicatif::ProgressBar;
+use rayon::ThreadPoolBuilder;
+use std::sync::Mutex;
+
+use super::cancel;
+use super::history::History;
+use super::TestResult;
+use crate::error::{Error, Result};
+use crate::settings::Options;
+
+/**
+Sorts tests, so the slowest ones (by time of their last run) come first.
+Tests without known time are treated as the slowest.
+*/
+pub fn sort_slowest_first<T, F>(jobs: &mut [T], history: &History, test: F)
+where
+    F: Fn(&T) -> &TestResult,
+{
+    jobs.sort_by_cached_key(|job| {
+        let time = history
+            .get(test(job).get_test_path())
+            .and_then(|past| past.time)
+            .unwrap_or(f32::INFINITY);
+        std::cmp::Reverse(time.to_bits())
+    });
+}
+
+/**
+Runs work on every job. Shared jobs are started strictly in given order, as threads of
+the pool become free, exclusive ones are run afterwards, one at a time. Work gets index of the job,
+which is unique among all jobs, and returns true when no more jobs should be started.
+Jobs are not started after the run was stopped (or interrupted with Ctrl+C) either.
+Order of jobs is changed (shared jobs come first). Error is returned when pool of threads
+can not be created, no job is run then.
+*/
+pub fn schedule<T, E, W>(jobs: &mut [T], settings: &Options, exclusive: E, work: W) -> Result<()>
+where
+    T: Send,
+    E: Fn(&T) -> bool,
+    W: Fn(&mut T, usize) -> bool + Sync,
+{
+    let work = |job: &mut T, index: usize| {
+        if !cancel::is_stopped() && work(job, index) {
+            cancel::stop();
+        }
+    };
+
+    jobs.sort_by_key(|job| exclusive(job));
+    let shared_count = jobs.iter().filter(|job| !exclusive(job)).count();
+    let (shared, exclusive) = jobs.split_at_mut(shared_count);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(settings.get_jobs())
+        .build()
+        .map_err(|e| Error::ThreadPool(e.to_string()))?;
+    let progress = ProgressBar::new((shared.len() + exclusive.len()) as u64);
+
+    // Every thread takes the next job from a shared queue, so jobs start in order.
+    let queue = Mutex::new(shared.iter_mut().enumerate());
+    pool.scope(|scope| {
+        for _ in 0..pool.current_num_threads() {
+            scope.spawn(|_| loop {
+                let next = queue
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .next();
+                match next {
+                    Some((index, job)) => {
+                        work(job, index);
+                        progress.inc(1);
+                    }
+                    None => break,
+                }
+            });
+        }
+    });
+
+    for (index, job) in exclusive.iter_mut().enumerate() {
+        work(job, shared_count + index);
+        progress.inc(1);
+    }
+    progress.finish();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_test() {
+        let mut jobs = vec![(1, true), (2, false), (3, true), (4, false)];
+        let finished = Mutex::new(Vec::new());
+
+        schedule(
+            &mut jobs,
+            &Options::default(),
+            |job| job.1,
+            |job, index| {
+                finished.lock().unwrap().push((job.0, index));
+                false
+            },
+        )
+        .unwrap();
+
+        let finished = finished.into_inner().unwrap();
+        assert_eq!(&finished[2..], &[(1, 2), (3, 3)]);
+        assert_eq!(jobs, vec![(2, false), (4, false), (1, true), (3, true)]);
+    }
+
+    #[test]
+    fn start_order_test() {
+        let mut jobs: Vec<(u32, bool)> = (0..64).map(|job| (job, false)).collect();
+        let started = Mutex::new(Vec::new());
+        let mut settings = Options::default();
+        settings.set_jobs(4);
+
+        schedule(
+            &mut jobs,
+            &settings,
+            |job| job.1,
+            |job, _| {
+                started.lock().unwrap().push(job.0);
+                false
+            },
+        )
+        .unwrap();
+
+        // A job is recorded after it was taken, so only threads racing for the lock reorder it.
+        let started = started.into_inner().unwrap();
+        assert_eq!(started.len(), 64);
+        for (position, job) in started.into_iter().enumerate() {
+            assert!((job as usize).abs_diff(position) < 4);
+        }
+    }
+}
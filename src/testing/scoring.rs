@@ -0,0 +1,88 @@
+//! Weighted scoring of test results, with partial credit and penalties.
+
+use super::manifest::Manifest;
+use super::TestResult;
+use crate::settings::Options;
+
+/// Final score of a test suite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub earned: f32,
+    pub maximum: f32,
+}
+
+/**
+Returns points earned by a single test. Passed test gets all of its points,
+multiplied by warnings credit if it was compiled with warnings. Test with correct output,
+which failed only because of valgrind, gets points multiplied by valgrind credit
+(or nothing, if manifest marks it as valgrind-required).
+*/
+pub fn test_score(result: &TestResult, manifest: &Manifest, settings: &Options) -> f32 {
+    let entry = manifest.entry(&result.get_name());
+
+    if result.passed() {
+        match result.get_compilation_warnings() {
+            Some(_) => entry.points * settings.get_warnings_credit(),
+            None => entry.points,
+        }
+    } else if result.valgrind_error() && result.output_correct() && !entry.valgrind_required {
+        entry.points * settings.get_valgrind_credit()
+    } else {
+        0.0
+    }
+}
+
+/**
+Returns score of given results out of points of all tests in the suite (given by names),
+so tests which were not run (stopped by fail-fast or Ctrl+C) count as failed.
+If manifest sets points of the whole suite, score is scaled to them.
+*/
+pub fn suite_score(
+    results: &[TestResult],
+    tests: &[String],
+    manifest: &Manifest,
+    settings: &Options,
+) -> Score {
+    let earned: f32 = results
+        .iter()
+        .map(|result| test_score(result, manifest, settings))
+        .sum();
+    let maximum: f32 = tests.iter().map(|name| manifest.entry(name).points).sum();
+
+    match manifest.get_suite_points() {
+        Some(suite) if maximum > 0.0 => Score {
+            earned: earned * suite / maximum,
+            maximum: suite,
+        },
+        _ => Score { earned, maximum },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suite_maximum_test() {
+        let manifest = Manifest::parse("suite points=10\nb points=3\n");
+        let results = [TestResult::new("tests/a.in")];
+        let tests = ["a.in".to_string(), "b.in".to_string()];
+
+        let score = suite_score(&results, &tests, &manifest, &Options::default());
+        assert_eq!(
+            score,
+            Score {
+                earned: 0.0,
+                maximum: 10.0
+            }
+        );
+        let score = suite_score(&results, &tests, &Manifest::default(), &Options::default());
+        assert_eq!(
+            score,
+            Score {
+                earned: 0.0,
+                maximum: 2.0
+            }
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Stress testing: inputs produced by a generator program are fed to a reference
+//! implementation and to the tested program until their outputs differ.
+
+use indicatif::ProgressBar;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Output;
+
+use super::cancel;
+use super::test_enums::DiffResult;
+use super::{run_with_input, TestResult};
+use crate::settings::Options;
+
+const EXPECTED_STDOUT: &str = "rtest_stress_expected_stdout";
+const EXPECTED_STDERR: &str = "rtest_stress_expected_stderr";
+const ACTUAL_STDOUT: &str = "rtest_stress_actual_stdout";
+const ACTUAL_STDERR: &str = "rtest_stress_actual_stderr";
+
+/// Possible outcomes of stress testing
+#[derive(Debug)]
+pub enum StressOutcome {
+    /// No mismatch was found in given number of iterations
+    Passed(u64),
+    /// Outputs differ for given seed, failing case was saved as a new test
+    Mismatch {
+        seed: u64,
+        saved_as: String,
+        diff: String,
+    },
+    Failed(String),
+}
+
+fn cleanup() {
+    for file in [
+        EXPECTED_STDOUT,
+        EXPECTED_STDERR,
+        ACTUAL_STDOUT,
+        ACTUAL_STDERR,
+    ] {
+        cancel::remove_temp(file);
+    }
+}
+
+/**
+Compares outputs of reference and tested program, returns diff of the first difference.
+*/
+fn compare(expected: &Output, actual: &Output, use_stderr: bool) -> io::Result<Option<String>> {
+    cancel::write_temp(EXPECTED_STDOUT, &expected.stdout)?;
+    cancel::write_temp(ACTUAL_STDOUT, &actual.stdout)?;
+    let mut pairs = vec![(ACTUAL_STDOUT, EXPECTED_STDOUT)];
+
+    if use_stderr {
+        cancel::write_temp(EXPECTED_STDERR, &expected.stderr)?;
+        cancel::write_temp(ACTUAL_STDERR, &actual.stderr)?;
+        pairs.push((ACTUAL_STDERR, EXPECTED_STDERR));
+    }
+
+    for (actual, expected) in pairs {
+        match TestResult::diff_files(actual, expected) {
+            DiffResult::Ok => {}
+            DiffResult::DifferenceNotSpecified(diff) => return Ok(Some(diff)),
+            other => return Err(io::Error::other(format!("{:?}", other))),
+        }
+    }
+    Ok(None)
+}
+
+/**
+Saves failing case as a new test (stress_SEED.in, .out and optionally .err) in test folder.
+Expected output comes from the reference implementation.
+*/
+fn save_case(settings: &Options, seed: u64, input: &[u8], expected: &Output) -> io::Result<String> {
+    let core = Path::new(settings.get_test_path()).join(format!("stress_{}", seed));
+    let core = core.to_string_lossy();
+
+    fs::write(format!("{}.in", core), input)?;
+    fs::write(format!("{}.out", core), &expected.stdout)?;
+    if settings.get_stderr_option() {
+        fs::write(format!("{}.err", core), &expected.stderr)?;
+    }
+    Ok(format!("{}.in", core))
+}
+
+fn stress_iteration(settings: &Options, seed: u64) -> io::Result<Option<StressOutcome>> {
+    let generator = [settings.get_generator_path().to_string(), seed.to_string()];
+    let generated = run_with_input(&generator, &[])?;
+    // Outputs of children killed by Ctrl+C would look like a failure.
+    cancel::check_interrupted()?;
+    if !generated.status.success() {
+        return Ok(Some(StressOutcome::Failed(format!(
+            "generator failed for seed {}: {}",
+            seed,
+            String::from_utf8_lossy(&generated.stderr)
+        ))));
+    }
+
+    let input = generated.stdout;
+
+    let reference = [settings.get_reference_path().to_string()];
+    let expected = run_with_input(&reference, &input)?;
+    let program = settings
+        .get_program_invocation()
+        .map_err(io::Error::other)?;
+    let actual = run_with_input(&program, &input)?;
+    cancel::check_interrupted()?;
+
+    match compare(&expected, &actual, settings.get_stderr_option())? {
+        None => Ok(None),
+        Some(diff) => {
+            let saved_as = save_case(settings, seed, &input, &expected)?;
+            Ok(Some(StressOutcome::Mismatch {
+                seed,
+                saved_as,
+                diff,
+            }))
+        }
+    }
+}
+
+/**
+Runs stress testing with settings' generator, reference implementation and number of
+iterations. Generator gets seed (1, 2, 3...) as its only argument and prints input on stdout.
+Stops at the first mismatch of outputs or when interrupted with Ctrl+C.
+*/
+pub fn run_stress(settings: &Options) -> StressOutcome {
+    if settings.get_program_mode() {
+        return StressOutcome::Failed("stress testing does not support compiled mode".to_string());
+    }
+
+    let iterations = settings.get_stress_iterations();
+    let progress = ProgressBar::new(iterations);
+    let _run = cancel::Run::start();
+
+    for seed in 1..=iterations {
+        let outcome = stress_iteration(settings, seed);
+        progress.inc(1);
+
+        match outcome {
+            Ok(None) => {}
+            Ok(Some(outcome)) => {
+                progress.finish_and_clear();
+                cleanup();
+                return outcome;
+            }
+            Err(e) => {
+                progress.finish_and_clear();
+                cleanup();
+                return StressOutcome::Failed(e.to_string());
+            }
+        }
+    }
+
+    progress.finish_and_clear();
+    cleanup();
+    StressOutcome::Passed(iterations)
+}
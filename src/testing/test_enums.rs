@@ -1,22 +1,45 @@
 /// Results which can occur while using diff
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum DiffResult {
+    /// Files are the same
     Ok,
+    /// Files differ, with output of diff
     DifferenceNotSpecified(String),
+    /// Stderr of the program differs from .err file
     DifferenceStderr(String),
+    /// Stdout of the program differs from .out file
     DifferenceStdout(String),
+    /// Exit code of the program differs from .code file
+    DifferenceExitCode(String),
+    /// Diff reported a problem (e.g. missing file), with its stderr
     Trouble(String),
+    /// Diff could not be run
     InnerProblem(String),
 }
 /// Possible causes of failing tests
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TestFail {
+    /// Valgrind found errors, with its report
     Valgrind(String),
+    /// Sanitizer found errors, with its report
+    Sanitizer(String),
+    /// Test driver did not compile, with output of compiler
     Compilation(String),
+    /// Compilation produced warnings treated as failures
+    CompilationWarnings(String),
+    /// Output of the program is not the expected one
     Diff(DiffResult),
+    /// Program used more memory than the limit from settings
+    MemoryLimit(String),
+    /// Program ran longer than the limit relative to reference implementation
+    TimeLimit(String),
+    /// Test could not be conducted (e.g. input could not be read)
     InnerProblem(String),
+    /// Program was terminated by a signal
     ProgramExitCode(),
+    /// Valgrind was terminated by a signal
     ValgrindExitCode(),
+    /// Compiler was terminated by a signal
     CompilationExitCode,
 }
 
@@ -27,6 +50,7 @@ impl TestFail {
     pub fn get_problem(&self) -> &str {
         match self {
             TestFail::Valgrind(err) => err,
+            TestFail::Sanitizer(err) => err,
             TestFail::InnerProblem(err) => err,
             TestFail::ProgramExitCode() => "Probably caused by unexpected exit of tested program.",
             TestFail::ValgrindExitCode() => {
@@ -35,11 +59,15 @@ impl TestFail {
             TestFail::Diff(diff_error) => match diff_error {
                 DiffResult::DifferenceStderr(err) => err,
                 DiffResult::DifferenceStdout(err) => err,
+                DiffResult::DifferenceExitCode(err) => err,
                 DiffResult::InnerProblem(err) => err,
                 DiffResult::Trouble(err) => err,
                 _ => "UNDEFINED BEHAVIOUR OF GET_PROBLEM FUNCTION",
             },
             TestFail::Compilation(comp_error) => comp_error,
+            TestFail::CompilationWarnings(warnings) => warnings,
+            TestFail::MemoryLimit(usage) => usage,
+            TestFail::TimeLimit(timings) => timings,
             TestFail::CompilationExitCode => {
                 "Probably caused by unexpected exit of compilation process (gcc)."
             }
@@ -1,9 +1,19 @@
 use std::fs::{self, File};
-use std::io::{self, Write};
+use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
 use std::process::{Command, Stdio};
+use std::time::Instant;
 
+use super::build_cache;
+use super::cancel;
+use super::measure::{self, Measurement};
+use super::pipeline::{Checker, Pipeline, RunOutput, Wrapper};
 use super::test_enums::{DiffResult, TestFail};
-use super::{is_cfile, is_infile};
+use super::time_limit::TimeLimit;
+use super::{is_infile, is_source_file};
+use crate::error::{Error, Result};
+use crate::settings::Options;
+
 /// Structure to manage testing
 #[derive(Debug)]
 pub struct TestResult {
@@ -12,7 +22,16 @@ pub struct TestResult {
     time: f32,
     failed_cause: TestFail,
     return_code: i32,
+    signal: Option<i32>,
+    usage: Option<Measurement>,
+    time_limit: Option<TimeLimit>,
+    attempts: u32,
+    flip_rate: Option<f32>,
+    finished: bool,
     compilation_warnings: Option<String>,
+    output_correct: bool,
+    program_stdout: Option<String>,
+    program_stderr: Option<String>,
 }
 
 impl PartialEq for TestResult {
@@ -25,7 +44,7 @@ impl Eq for TestResult {}
 
 impl PartialOrd for TestResult {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.test_path.partial_cmp(&other.test_path)
+        Some(self.cmp(other))
     }
 }
 
@@ -39,21 +58,44 @@ impl TestResult {
     /**
     Creates new structure with given absolute path of test
     */
-    fn new(path: &str) -> TestResult {
+    pub(super) fn new(path: &str) -> TestResult {
         TestResult {
             test_path: path.to_string(),
             passed: false,
             time: 0.0,
             failed_cause: TestFail::InnerProblem("".to_string()),
             return_code: 5,
+            signal: None,
+            usage: None,
+            time_limit: None,
+            attempts: 1,
+            flip_rate: None,
+            finished: false,
+            program_stdout: None,
+            program_stderr: None,
             compilation_warnings: None,
+            output_correct: false,
         }
     }
 
+    /// Returns exit code of tested program from the last run
     pub fn get_exit_code(&self) -> i32 {
         self.return_code
     }
 
+    /**
+    Returns resources (CPU times and peak memory) used by tested program. Runs with
+    valgrind are not measured, as the numbers would describe valgrind itself.
+    */
+    pub fn get_usage(&self) -> Option<&Measurement> {
+        self.usage.as_ref()
+    }
+
+    /// Returns signal which terminated tested program, if any
+    pub fn get_signal(&self) -> Option<i32> {
+        self.signal
+    }
+
     /// Returns wheter there occured valgrind error while testing (true - occured).
     /// WARNING: It should be used only on struct, which was tested in the past
     pub fn valgrind_error(&self) -> bool {
@@ -66,45 +108,76 @@ impl TestResult {
         matches!(self.failed_cause, TestFail::Diff(_))
     }
 
+    /// Returns wheter test failed because of relative time limit (true - failed).
+    /// WARNING: It should be used only on struct, which was tested in the past.
+    pub fn time_limit_error(&self) -> bool {
+        matches!(self.failed_cause, TestFail::TimeLimit(_))
+    }
+
+    /// Returns wheter test failed because of memory limit (true - failed).
+    /// WARNING: It should be used only on struct, which was tested in the past.
+    pub fn memory_limit_error(&self) -> bool {
+        matches!(self.failed_cause, TestFail::MemoryLimit(_))
+    }
+
+    /// Returns wheter test failed because of compilation warnings (true - failed).
+    /// WARNING: It should be used only on struct, which was tested in the past.
+    pub fn warnings_error(&self) -> bool {
+        matches!(self.failed_cause, TestFail::CompilationWarnings(_))
+    }
+
+    /// Returns warnings produced while compiling the test, if there were any
+    pub fn get_compilation_warnings(&self) -> Option<&str> {
+        self.compilation_warnings.as_deref()
+    }
+
+    /// Returns wheter output of the program matched expected one (true - matched), even if
+    /// the test failed for other reasons (e.g. valgrind errors).
+    /// WARNING: It should be used only on struct, which was tested in the past.
+    pub fn output_correct(&self) -> bool {
+        self.output_correct
+    }
+
     /**
     Creates a vector of TestResults from every single file with .in extension
     in given absolue path.
     */
-    pub(super) fn load(path: &str) -> Vec<TestResult> {
-        let source = fs::read_dir(path).expect("ERROR: Opening directory with tests FAILED.");
+    pub(super) fn load(path: &str) -> Result<Vec<TestResult>> {
+        let source = fs::read_dir(path).map_err(Error::io(path))?;
 
         let mut result = Vec::new();
         for file in source {
-            let entry = file.expect("ERROR: Reading tests FAILED.");
+            let entry = file.map_err(Error::io(path))?;
             if is_infile(&entry) {
-                result.push(TestResult::new(entry.path().to_str().unwrap()));
+                result.push(TestResult::new(&entry.path().to_string_lossy()));
             }
         }
-        result
+        Ok(result)
     }
 
     /**
-    Creates a vector of TestResults from every single file with .c extension
-    in given absolue path.
+    Creates a vector of TestResults from every single test driver (file with extension
+    which has a toolchain in settings) in test folder.
     */
-    pub(super) fn load_c(path: &str) -> Vec<TestResult> {
-        let source = fs::read_dir(path).expect("ERROR: Opening directory with tests FAILED.");
+    pub(super) fn load_sources(settings: &Options) -> Result<Vec<TestResult>> {
+        let path = settings.get_test_path();
+        let source = fs::read_dir(path).map_err(Error::io(path))?;
 
         let mut result = Vec::new();
         for file in source {
-            let entry = file.expect("ERROR: Reading tests FAILED.");
-            if is_cfile(&entry) {
-                result.push(TestResult::new(entry.path().to_str().unwrap()));
+            let entry = file.map_err(Error::io(path))?;
+            if is_source_file(&entry, settings) {
+                result.push(TestResult::new(&entry.path().to_string_lossy()));
             }
         }
-        result
+        Ok(result)
     }
 
     /**
     Returns a whole path without extension.
     EXAMPLE: test_path = /usr/bin/abc.de -> /usr/bin/abc
     */
-    fn get_core(&self) -> String {
+    pub(super) fn get_core(&self) -> String {
         let mut result = String::new();
         let mut iterator = self.test_path.split('.');
         result.push_str(iterator.next().unwrap());
@@ -115,7 +188,7 @@ impl TestResult {
     Returns a path of .out file.
     EXAMPLE -> test_path = /usr/bin/abc.de -> /usr/bin/abc.out
     */
-    fn get_stdout_file(&self) -> String {
+    pub fn get_stdout_file(&self) -> String {
         let mut result = self.get_core();
         result.push_str(".out");
         result
@@ -124,11 +197,12 @@ impl TestResult {
     /// Returns a name of testfile.
     /// EXAMPLE -> test_path = /usr/bin/abc.de -> abc.in
     pub fn get_name(&self) -> String {
-        let iterator = self.test_path.split('/');
-        let test_path = iterator.last().unwrap();
+        let mut iterator = self.test_path.split('/');
+        let test_path = iterator.next_back().unwrap();
         test_path.to_string()
     }
 
+    /// Returns time of the test in seconds (with compilation and diffing)
     pub fn get_time(&self) -> f32 {
         self.time
     }
@@ -137,12 +211,131 @@ impl TestResult {
     Returns a path of .err file.
     EXAMPLE -> test_path = /usr/bin/abc.de -> /usr/bin/abc.err
     */
-    fn get_stderr_file(&self) -> String {
+    pub fn get_stderr_file(&self) -> String {
         let mut result = self.get_core();
         result.push_str(".err");
         result
     }
 
+    /**
+    Returns a path of .code file, which holds expected exitcode.
+    EXAMPLE -> test_path = /usr/bin/abc.de -> /usr/bin/abc.code
+    */
+    fn get_code_file(&self) -> String {
+        let mut result = self.get_core();
+        result.push_str(".code");
+        result
+    }
+
+    /// Returns exitcode saved in .code file, if the file exists and is correct
+    pub fn get_expected_exit_code(&self) -> Option<i32> {
+        fs::read_to_string(self.get_code_file())
+            .ok()
+            .and_then(|code| code.trim().parse().ok())
+    }
+
+    /// Returns absolute path of test file
+    pub fn get_test_path(&self) -> &str {
+        &self.test_path
+    }
+
+    /// Returns number of times the test was run (more than one when it was retried)
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Returns true when test failed at first, but passed on retry
+    pub fn flaky(&self) -> bool {
+        self.passed && self.attempts > 1
+    }
+
+    /// Returns part of consecutive attempts with different outcomes, from history of runs
+    pub fn get_flip_rate(&self) -> Option<f32> {
+        self.flip_rate
+    }
+
+    pub(super) fn set_flip_rate(&mut self, flip_rate: Option<f32>) {
+        self.flip_rate = flip_rate;
+    }
+
+    /**
+    Runs the test again, while it fails, at most retries times. Result of the last
+    attempt replaces the current one, time limit of the test is kept for every attempt.
+    */
+    pub(super) fn retry<F>(&mut self, retries: u32, mut run: F)
+    where
+        F: FnMut(&mut TestResult),
+    {
+        for _ in 0..retries {
+            if self.passed {
+                break;
+            }
+
+            let mut attempt = TestResult::new(&self.test_path);
+            attempt.time_limit = self.time_limit;
+            run(&mut attempt);
+            attempt.attempts = self.attempts + 1;
+            *self = attempt;
+        }
+    }
+
+    /// Returns stderr of tested program (with valgrind report) from the last run
+    pub fn get_program_stderr(&self) -> Option<&str> {
+        self.program_stderr.as_deref()
+    }
+
+    /// Returns paths of existing files of the test (.in or test driver, .out, .err, .code)
+    pub fn get_test_files(&self) -> Vec<String> {
+        let mut files = vec![self.test_path.clone()];
+        files.extend(
+            [
+                self.get_stdout_file(),
+                self.get_stderr_file(),
+                self.get_code_file(),
+            ]
+            .into_iter()
+            .filter(|file| std::path::Path::new(file).is_file()),
+        );
+        files
+    }
+
+    /**
+    Returns side-by-side diff (expected output on the left) of expected and actual stdout,
+    with given width of the whole line. None when program was not run or .out file is missing.
+    */
+    pub fn side_by_side_diff(&self, width: usize) -> Option<String> {
+        let actual = self.program_stdout.as_ref()?;
+        let expected = self.get_stdout_file();
+        if !std::path::Path::new(&expected).is_file() {
+            return None;
+        }
+
+        let actual_file = format!("rtest_side_by_side{}", std::process::id());
+        fs::write(&actual_file, actual).ok()?;
+        cancel::track(&actual_file);
+        let output = Command::new("diff")
+            .arg("--side-by-side")
+            .arg("--expand-tabs")
+            .arg(format!("--width={}", width))
+            .arg(&expected)
+            .arg(&actual_file)
+            .output();
+        cancel::remove_temp(&actual_file);
+
+        output
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Returns true when the test was run to the end (it was not skipped or interrupted)
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub(super) fn set_finished(&mut self) {
+        self.finished = true;
+    }
+
     /// Returns true when test was succesfully done.
     /// WARNING: It should be used only on struct, which was tested in the past.
     pub fn passed(&self) -> bool {
@@ -150,159 +343,269 @@ impl TestResult {
     }
 
     /**
-    Runs a program without valgrind and saves its stdout and stderr to a file in main directory,
-    which is based of index. Index should be an unique number to all of the conducted tests.
+    Runs tested program with given command under the wrapper and saves its stdout and stderr
+    to a file in main directory, which is based of index. Index should be an unique number
+    to all of the conducted tests. Returns false when the run failed.
     */
-    fn run_program(&mut self, index: usize, program: &str) -> bool {
+    fn run_program(
+        &mut self,
+        index: usize,
+        command: Vec<String>,
+        wrapper: &dyn Wrapper,
+    ) -> Result<bool> {
         let divert_output = format!("rtest_stdout{}", index);
         let divert_error = format!("rtest_stderr{}", index);
-        let mut input_file = File::open(&self.test_path).expect("Failed to open input");
-        let mut output_file = File::create(divert_output).expect("Failed to open outputFile");
-        let mut error_file = File::create(divert_error).expect("Failed to create errorFile");
+        let input = fs::read(&self.test_path).map_err(Error::io(&self.test_path))?;
+        let mut output_file = File::create(&divert_output).map_err(Error::io(&divert_output))?;
+        cancel::track(&divert_output);
+        let mut error_file = File::create(&divert_error).map_err(Error::io(&divert_error))?;
+        cancel::track(&divert_error);
+
+        let command = wrapper.command(command);
+        let program = match command.first() {
+            Some(program) => program.clone(),
+            None => {
+                return Err(Error::Config(
+                    "command of tested program is empty".to_string(),
+                ))
+            }
+        };
+        let deadline = match self.time_limit {
+            Some(limit) if wrapper.measured() => Some(limit.deadline()),
+            _ => None,
+        };
+        let (output, usage) =
+            measure::run_measured_with(&command, &input, &wrapper.environment(), deadline)
+                .map_err(Error::process(program))?;
+        if wrapper.measured() {
+            self.usage = Some(usage);
+        }
 
-        let mut process = Command::new(program)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("ERROR: Spawning child process of Program FAILED.");
+        output_file
+            .write_all(&output.stdout)
+            .map_err(Error::io(&divert_output))?;
+        error_file
+            .write_all(&output.stderr)
+            .map_err(Error::io(&divert_error))?;
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        self.program_stdout = Some(String::from_utf8_lossy(&output.stdout).to_string());
+        self.program_stderr = Some(stderr.clone());
+
+        if let (true, Some(limit)) = (usage.timed_out, self.time_limit) {
+            self.failed_cause = TestFail::TimeLimit(format!(
+                "Program was killed after {:.3} s, limit is {:.3} s of CPU time ({} x reference time {:.3} s).",
+                usage.wall,
+                limit.cpu(),
+                limit.factor,
+                limit.reference
+            ));
+            return Ok(false);
+        }
 
-        io::copy(&mut input_file, process.stdin.as_mut().unwrap()).unwrap();
+        match output.status.code() {
+            Some(exit_code) => {
+                self.return_code = exit_code;
+                match wrapper.errors(exit_code, &stderr) {
+                    Some(cause) => {
+                        self.failed_cause = cause;
+                        Ok(false)
+                    }
+                    None => Ok(true),
+                }
+            }
+            None => {
+                self.signal = output.status.signal();
+                self.failed_cause = wrapper.terminated();
+                Ok(false)
+            }
+        }
+    }
 
-        let process = process.wait_with_output();
+    /**
+    Returns outcome of a step of testing. Error of the step is recorded as an inner problem
+    of the test, which fails then.
+    */
+    fn record_error(&mut self, step: Result<bool>) -> bool {
+        step.unwrap_or_else(|e| {
+            self.failed_cause = TestFail::InnerProblem(e.to_string());
+            false
+        })
+    }
 
-        match process {
-            Err(e) => {
-                self.failed_cause = TestFail::InnerProblem(e.to_string());
+    /// Sets time limit of the test, relative to runtime of the reference implementation
+    pub(super) fn set_time_limit(&mut self, limit: TimeLimit) {
+        self.time_limit = Some(limit);
+    }
+
+    /**
+    Returns false and sets cause of failure when CPU time used in the last run exceeds
+    time limit of the test (factor times CPU time of the reference implementation).
+    */
+    fn check_time_limit(&mut self) -> bool {
+        match (self.usage, self.time_limit) {
+            (Some(usage), Some(limit)) if usage.cpu() > limit.cpu() => {
+                self.failed_cause = TestFail::TimeLimit(format!(
+                    "Program used {:.3} s of CPU time, limit is {:.3} s ({} x reference time {:.3} s).",
+                    usage.cpu(),
+                    limit.cpu(),
+                    limit.factor,
+                    limit.reference
+                ));
                 false
             }
-            Ok(output) => {
-                let status = output.status.code();
+            _ => true,
+        }
+    }
 
-                if let Some(exit_code) = status {
-                    let stdout_result = String::from_utf8_lossy(&output.stdout).to_string();
-                    let stderr_result = String::from_utf8_lossy(&output.stderr).to_string();
-                    write!(&mut output_file, "{}", stdout_result).unwrap();
-                    write!(&mut error_file, "{}", stderr_result).unwrap();
-                    self.return_code = exit_code;
-                    true
-                } else {
-                    self.failed_cause = TestFail::ProgramExitCode();
-                    false
-                }
+    /**
+    Returns false and sets memory limit verdict when peak memory usage of the program
+    exceeds limit from settings (0 means no limit). Runs which are not measured (e.g. with
+    valgrind, whose memory would be checked instead) are not limited.
+    */
+    fn check_memory_limit(&mut self, settings: &Options) -> bool {
+        let limit = settings.get_memory_limit();
+        match self.usage {
+            Some(usage) if limit > 0 && usage.max_rss_kb > limit => {
+                self.failed_cause = TestFail::MemoryLimit(format!(
+                    "Peak memory usage {} kB exceeds limit {} kB.",
+                    usage.max_rss_kb, limit
+                ));
+                false
             }
+            _ => true,
         }
     }
 
     /**
-    Compiles a program to be tested. Program_path should be precompiled .o library.
+    Compiles a program to be tested with toolchain matching extension of the test driver
+    and returns a path of produced executable. Program path should point to precompiled
+    .o library. When build cache is active, executable built earlier from the same files
+    and command is reused.
     */
-    fn compile_program(&mut self, program_path: &str, index: usize) -> bool {
+    pub(super) fn compile_program(&mut self, settings: &Options, index: usize) -> Option<String> {
         let compiled_program = format!("rtest_compilation{}", index);
+        let toolchain = std::path::Path::new(&self.test_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| settings.get_toolchain(extension));
+        let toolchain = match toolchain {
+            Some(toolchain) if !toolchain.is_empty() => toolchain,
+            Some(_) => {
+                self.failed_cause =
+                    TestFail::InnerProblem("Toolchain of the test is empty.".to_string());
+                return None;
+            }
+            None => {
+                self.failed_cause =
+                    TestFail::InnerProblem("No toolchain for extension of the test.".to_string());
+                return None;
+            }
+        };
+        let command = expand_toolchain(
+            toolchain,
+            &self.test_path,
+            settings.get_program_path(),
+            &compiled_program,
+        );
+
+        // Output path depends on the thread index, so it is left out of the key.
+        let cache_key = if settings.get_build_cache_usage() {
+            let keyed = expand_toolchain(
+                toolchain,
+                &self.test_path,
+                settings.get_program_path(),
+                "{output}",
+            );
+            build_cache::cache_key(&[&self.test_path, settings.get_program_path()], &keyed).ok()
+        } else {
+            None
+        };
 
-        let process = Command::new("gcc")
-            .arg("-O2")
-            .arg("-Wall")
-            .arg("-Wextra")
-            .arg("-Wno-implicit-fallthrough")
-            .arg(&self.test_path)
-            .arg(program_path)
-            .arg("-o")
-            .arg(compiled_program)
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("ERROR: Spawning child process of GCC FAILED.");
-
-        let process = process.wait_with_output();
+        if let Some((binary, warnings)) = cache_key.as_deref().and_then(build_cache::lookup) {
+            return self.check_warnings(settings, warnings).then_some(binary);
+        }
 
-        match process {
+        cancel::track(&compiled_program);
+        match measure::run_measured(&command, &[]) {
             Err(e) => {
-                self.failed_cause = TestFail::InnerProblem(e.to_string());
-                false
+                cancel::remove_temp(&compiled_program);
+                self.failed_cause =
+                    TestFail::InnerProblem(Error::process(&command[0])(e).to_string());
+                None
             }
-            Ok(output) => {
+            Ok((output, _)) => {
                 let status = output.status.code();
 
                 if let Some(exit_code) = status {
                     match exit_code {
-                        1 => {
-                            let failed_result = String::from_utf8_lossy(&output.stderr).to_string();
-                            self.failed_cause = TestFail::Compilation(failed_result);
-                            false
-                        }
-                        _ => {
+                        0 => {
                             let compilation_warning =
                                 String::from_utf8_lossy(&output.stderr).to_string();
-                            self.compilation_warnings = Some(compilation_warning);
-                            true
+                            let cached = cache_key.and_then(|key| {
+                                let owner =
+                                    format!("{}\n{}", self.test_path, settings.get_program_path());
+                                build_cache::store(
+                                    &key,
+                                    &owner,
+                                    &compiled_program,
+                                    &compilation_warning,
+                                )
+                                .ok()
+                            });
+                            let binary = match cached {
+                                Some(binary) => {
+                                    // Executable was moved to the cache.
+                                    cancel::untrack(&compiled_program);
+                                    binary
+                                }
+                                None => compiled_program,
+                            };
+
+                            if self.check_warnings(settings, compilation_warning) {
+                                Some(binary)
+                            } else {
+                                TestResult::remove_compiled(&binary);
+                                None
+                            }
+                        }
+                        _ => {
+                            cancel::remove_temp(&compiled_program);
+                            let failed_result = String::from_utf8_lossy(&output.stderr).to_string();
+                            self.failed_cause = TestFail::Compilation(failed_result);
+                            None
                         }
                     }
                 } else {
+                    cancel::remove_temp(&compiled_program);
                     self.failed_cause = TestFail::CompilationExitCode;
-                    false
+                    None
                 }
             }
         }
     }
 
     /**
-    Runs a program with valgrind and saves its stdout and stderr to a file in main directory,
-    which is based of index. Index should be an unique number to all of the conducted tests.
+    Saves compilation warnings and returns false when, according to settings,
+    they should fail the test.
     */
-    fn run_valgrind(&mut self, index: usize, program: &str) -> bool {
-        let divert_output = format!("rtest_stdout{}", index);
-        let divert_error = format!("rtest_stderr{}", index);
-        let mut input_file = File::open(&self.test_path).expect("Failed to open input");
-        let mut output_file = File::create(divert_output).expect("Failed to open outputFile");
-        let mut error_file = File::create(divert_error).expect("Failed to create errorFile");
-
-        let mut process = Command::new("valgrind")
-            .arg("--leak-check=full")
-            .arg("--error-exitcode=-573")
-            .arg("--show-leak-kinds=all")
-            .arg("--errors-for-leak-kinds=all")
-            .arg("-q")
-            .arg(program)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("ERROR: Spawning child process of Valgrind FAILED.");
-
-        io::copy(&mut input_file, process.stdin.as_mut().unwrap()).unwrap();
-        let process = process.wait_with_output();
-
-        match process {
-            Err(e) => {
-                self.failed_cause = TestFail::InnerProblem(e.to_string());
-                false
-            }
-            Ok(output) => {
-                let status = output.status.code();
+    fn check_warnings(&mut self, settings: &Options, warnings: String) -> bool {
+        if warnings.trim().is_empty() {
+            return true;
+        }
 
-                if let Some(exit_code) = status {
-                    match exit_code {
-                        -573 => {
-                            let failed_result = String::from_utf8_lossy(&output.stderr).to_string();
-                            self.failed_cause = TestFail::Valgrind(failed_result);
-                            false
-                        }
+        let failing = settings.get_warnings_as_failures()
+            && warnings_match(&warnings, settings.get_warning_filter());
+        if failing {
+            self.failed_cause = TestFail::CompilationWarnings(warnings.clone());
+        }
+        self.compilation_warnings = Some(warnings);
+        !failing
+    }
 
-                        return_code => {
-                            let stdout_result = String::from_utf8_lossy(&output.stdout).to_string();
-                            let stderr_result = String::from_utf8_lossy(&output.stderr).to_string();
-                            write!(&mut output_file, "{}", stdout_result).unwrap();
-                            write!(&mut error_file, "{}", stderr_result).unwrap();
-                            self.return_code = return_code;
-                            println!("OUTPUT FILE {}", stdout_result);
-                            true
-                        }
-                    }
-                } else {
-                    self.failed_cause = TestFail::ValgrindExitCode();
-                    false
-                }
-            }
+    /// Removes an executable produced by compile_program, unless it is kept in build cache
+    pub(super) fn remove_compiled(path: &str) {
+        if !build_cache::is_cached(path) {
+            cancel::remove_temp(path);
         }
     }
 
@@ -310,7 +613,7 @@ impl TestResult {
     Compares two files using diff program. input_diff indicates path to program generated file
     output_diff indicates path to template file
     */
-    fn diff_files(input_diff: &str, output_diff: &str) -> DiffResult {
+    pub fn diff_files(input_diff: &str, output_diff: &str) -> DiffResult {
         let process = Command::new("diff")
             .arg("-c")
             .arg(input_diff)
@@ -321,184 +624,101 @@ impl TestResult {
 
         match process {
             Err(e) => DiffResult::InnerProblem(e.to_string()),
-            Ok(output) => {
-                let status = output
-                    .status
-                    .code()
-                    .expect("ERROR: Reading exitcode of diff FAILED.");
-                match status {
-                    0 => DiffResult::Ok,
-                    1 => {
-                        let diff_result = String::from_utf8_lossy(&output.stdout).to_string();
-                        DiffResult::DifferenceNotSpecified(diff_result)
-                    }
-                    _ => {
-                        let diff_error = String::from_utf8_lossy(&output.stderr).to_string();
-                        DiffResult::Trouble(diff_error)
-                    }
+            Ok(output) => match output.status.code() {
+                None => DiffResult::InnerProblem(Error::NoExitCode("diff".to_string()).to_string()),
+                Some(0) => DiffResult::Ok,
+                Some(1) => {
+                    let diff_result = String::from_utf8_lossy(&output.stdout).to_string();
+                    DiffResult::DifferenceNotSpecified(diff_result)
                 }
-            }
+                Some(_) => {
+                    let diff_error = String::from_utf8_lossy(&output.stderr).to_string();
+                    DiffResult::Trouble(diff_error)
+                }
+            },
         }
     }
 
     /**
-    Conducts a test process with compilation and valgrind usage.
+    Conducts a test process with steps of given pipeline: prepares tested program, runs it
+    under the wrapper and checks its output. Index should be an unique number to all of
+    the tests conducted at the same time.
     */
-    pub(super) fn test_compiled_with_valgrind(
-        &mut self,
-        program_path: &str,
-        index: usize,
-        use_stderr: bool,
-    ) {
-        use std::time::SystemTime;
-        let beggining = SystemTime::now();
-
-        if !self.compile_program(program_path, index) {
-            self.passed = false;
-            return;
-        }
+    pub(super) fn conduct(&mut self, pipeline: &Pipeline, settings: &Options, index: usize) {
+        let beggining = Instant::now();
+
+        let executable = match pipeline.get_build_step().build(self, settings, index) {
+            Ok(executable) => executable,
+            Err(cause) => {
+                self.failed_cause = cause;
+                self.passed = false;
+                return;
+            }
+        };
 
-        let compiled_path = format!("./rtest_compilation{}", index);
-        if self.run_valgrind(index, &compiled_path) && self.run_diff(index, use_stderr) {
-            self.passed = true;
+        let ran = self.run_program(index, executable.command, pipeline.get_wrapper());
+        if self.record_error(ran) && self.check_memory_limit(settings) && self.check_time_limit() {
+            let exit_code = Some(self.return_code);
+            self.passed = self.check_output(index, pipeline.get_checker(), settings, exit_code);
+        } else if self.wrapper_error() {
+            // Output is still checked, so it can give partial credit. Exit code is the one
+            // of the wrapper reporting errors, so it is not compared.
+            let cause = std::mem::replace(
+                &mut self.failed_cause,
+                TestFail::InnerProblem("".to_string()),
+            );
+            self.check_output(index, pipeline.get_checker(), settings, None);
+            self.failed_cause = cause;
         }
 
         let stdout = format!("rtest_stdout{}", index);
         let stderr = format!("rtest_stderr{}", index);
-        let compiled = format!("rtest_compilation{}", index);
-        let _ = fs::remove_file(stdout);
-        let _ = fs::remove_file(stderr);
-        let _ = fs::remove_file(compiled);
-
-        self.time = SystemTime::now()
-            .duration_since(beggining)
-            .unwrap_or_else(|_| panic!("ERROR: Time_calculation of {:?} FAILED.", self.test_path))
-            .as_secs_f32();
-    }
-
-    /**
-    Conducts a test process with compilation and without valgrind usage.
-    */
-    pub(super) fn test_compiled_no_valgrind(
-        &mut self,
-        program_path: &str,
-        index: usize,
-        use_stderr: bool,
-    ) {
-        use std::time::SystemTime;
-        let beggining = SystemTime::now();
-
-        if !self.compile_program(program_path, index) {
-            self.passed = false;
-            return;
+        cancel::remove_temp(&stdout);
+        cancel::remove_temp(&stderr);
+        if let Some(produced) = executable.produced {
+            TestResult::remove_compiled(&produced);
         }
 
-        let compiled_path = format!("./rtest_compiled{}", index);
-        if self.run_program(index, &compiled_path) && self.run_diff(index, use_stderr) {
-            self.passed = true;
-        }
-
-        let stdout = format!("rtest_stdout{}", index);
-        let stderr = format!("rtest_stderr{}", index);
-        let compiled = format!("rtest_compilation{}", index);
-        let _ = fs::remove_file(stdout);
-        let _ = fs::remove_file(stderr);
-        let _ = fs::remove_file(compiled);
+        self.time = beggining.elapsed().as_secs_f32();
+    }
 
-        self.time = SystemTime::now()
-            .duration_since(beggining)
-            .unwrap_or_else(|_| panic!("ERROR: Time_calculation of {:?} FAILED.", self.test_path))
-            .as_secs_f32();
+    /// Returns true when execution wrapper (valgrind or sanitizer) found errors in the last run
+    fn wrapper_error(&self) -> bool {
+        matches!(
+            self.failed_cause,
+            TestFail::Valgrind(_) | TestFail::Sanitizer(_)
+        )
     }
 
     /**
-    Conducts a test process with using valgrind
+    Checks output and exit code (if known) of the last run with given checker. Index
+    indicate test index. Returns false and sets cause of failure when output is not
+    the expected one.
     */
-    pub(super) fn test_with_valgrind(
+    fn check_output(
         &mut self,
-        program_path: &str,
         index: usize,
-        use_stderr: bool,
-    ) {
-        use std::time::SystemTime;
-        let beggining = SystemTime::now();
-        //println!("THREAD {} RUN", self.get_name());
-        if self.run_valgrind(index, program_path) && self.run_diff(index, use_stderr) {
-            self.passed = true;
-        }
-
-        let stdout = format!("rtest_stdout{}", index);
-        let stderr = format!("rtest_stderr{}", index);
-        let _ = fs::remove_file(stdout);
-        let _ = fs::remove_file(stderr);
-
-        self.time = SystemTime::now()
-            .duration_since(beggining)
-            .unwrap_or_else(|_| panic!("ERROR: Time_calculation of {:?} FAILED.", self.test_path))
-            .as_secs_f32();
-    }
-
-    /**
-    Conducts a test process without using valgrind
-    */
-    pub(super) fn test_no_valgrind(&mut self, program_path: &str, index: usize, use_stderr: bool) {
-        use std::time::SystemTime;
-        let beggining = SystemTime::now();
-        //println!("THREAD {} RUN", self.get_name());
-
-        if self.run_program(index, program_path) && self.run_diff(index, use_stderr) {
-            self.passed = true;
-        }
-
-        let stdout = format!("rtest_stdout{}", index);
-        let stderr = format!("rtest_stderr{}", index);
-        let _ = fs::remove_file(stdout);
-        let _ = fs::remove_file(stderr);
-
-        self.time = SystemTime::now()
-            .duration_since(beggining)
-            .unwrap_or_else(|_| panic!("ERROR: Time calculation of {:?} FAILED.", self.test_path))
-            .as_secs_f32();
-    }
+        checker: &dyn Checker,
+        settings: &Options,
+        exit_code: Option<i32>,
+    ) -> bool {
+        let stdout_file = format!("rtest_stdout{}", index);
+        let stderr_file = format!("rtest_stderr{}", index);
+        let output = RunOutput {
+            stdout_file: &stdout_file,
+            stderr_file: &stderr_file,
+            exit_code,
+        };
 
-    /**
-    Runs diff on program output. Index indicate test index
-    */
-    fn run_diff(&mut self, index: usize, use_stderr: bool) -> bool {
-        let stdout_input = format!("rtest_stdout{}", index);
-        let stderr_input = format!("rtest_stderr{}", index);
-        let stdout_output = self.get_stdout_file();
-        let stderr_output = self.get_stderr_file();
-
-        let stdout_result = TestResult::diff_files(&stdout_input, &stdout_output);
-
-        match stdout_result {
-            DiffResult::Ok => {}
-            DiffResult::DifferenceNotSpecified(error) => {
-                self.failed_cause = TestFail::Diff(DiffResult::DifferenceStdout(error));
-                return false;
-            }
-            other => {
-                self.failed_cause = TestFail::Diff(other);
-                return false;
+        match checker.check(self, &output, settings) {
+            Ok(()) => {
+                self.output_correct = true;
+                true
             }
-        }
-
-        if use_stderr {
-            let stderr_result = TestResult::diff_files(&stderr_input, &stderr_output);
-
-            match stderr_result {
-                DiffResult::Ok => true,
-                DiffResult::DifferenceNotSpecified(error) => {
-                    self.failed_cause = TestFail::Diff(DiffResult::DifferenceStderr(error));
-                    false
-                }
-                _ => {
-                    panic!("UNABLE ARM OF RUN DIFF REACHED!! (STDERR)");
-                }
+            Err(cause) => {
+                self.failed_cause = cause;
+                false
             }
-        } else {
-            true
         }
     }
 
@@ -509,7 +729,11 @@ impl TestResult {
             TestFail::ValgrindExitCode() => "SYSTEM: Valgrind EXITCODE read failed!".to_string(),
             TestFail::CompilationExitCode => "SYSTEM: Gcc EXITCODE read failed!".to_string(),
             TestFail::Valgrind(_) => "Valgrind ERROR".to_string(),
+            TestFail::Sanitizer(_) => "Sanitizer ERROR".to_string(),
             TestFail::Compilation(_) => "Compilation ERROR".to_string(),
+            TestFail::CompilationWarnings(_) => "Compilation WARNINGS".to_string(),
+            TestFail::MemoryLimit(_) => "Memory limit EXCEEDED".to_string(),
+            TestFail::TimeLimit(_) => "Time limit EXCEEDED".to_string(),
             TestFail::Diff(diff_error) => match diff_error {
                 DiffResult::DifferenceNotSpecified(_) => {
                     "Diff ERROR: Difference (not specified)".to_string()
@@ -517,6 +741,9 @@ impl TestResult {
                 DiffResult::DifferenceStderr(_) => "Diff ERROR: Difference (stderr)".to_string(),
                 DiffResult::DifferenceStdout(_) => "Diff ERROR: Difference (stdout)".to_string(),
                 DiffResult::InnerProblem(_) => "Diff ERROR: InnerProblem".to_string(),
+                DiffResult::DifferenceExitCode(_) => {
+                    "Diff ERROR: Difference (exitcode)".to_string()
+                }
                 DiffResult::Trouble(_) => "Diff ERROR: Trouble".to_string(),
                 _ => "PROGRAM UNDEFINED DIFF ERROR".to_string(),
             },
@@ -528,6 +755,40 @@ impl TestResult {
     pub fn get_problem(&self) -> &str {
         self.failed_cause.get_problem()
     }
+
+    /// Returns cause of failure of the test
+    pub(super) fn get_failed_cause(&self) -> &TestFail {
+        &self.failed_cause
+    }
+}
+
+/**
+Builds compiler command from toolchain template, replacing {source}, {program}
+and {output} placeholders in every argument.
+*/
+fn expand_toolchain(
+    toolchain: &[String],
+    source: &str,
+    program: &str,
+    output: &str,
+) -> Vec<String> {
+    toolchain
+        .iter()
+        .map(|arg| {
+            arg.replace("{source}", source)
+                .replace("{program}", program)
+                .replace("{output}", output)
+        })
+        .collect()
+}
+
+/// Checks wheter compiler output contains a warning enabled by one of given flags
+/// (e.g. -Wunused-variable). Empty filter matches every warning.
+fn warnings_match(warnings: &str, filter: &[String]) -> bool {
+    warnings
+        .lines()
+        .filter(|line| line.contains("warning:"))
+        .any(|line| filter.is_empty() || filter.iter().any(|flag| line.contains(flag.as_str())))
 }
 
 #[cfg(test)]
@@ -536,43 +797,75 @@ mod tests {
 
     #[test]
     fn get_stdout_test() {
-        let ts = TestResult {
-            test_path: "/usr/bin/a/b/c/def.in".to_string(),
-            passed: false,
-            time: 0.0,
-            failed_cause: TestFail::InnerProblem("".to_string()),
-            return_code: 1,
-            compilation_warnings: None,
-        };
+        let ts = TestResult::new("/usr/bin/a/b/c/def.in");
 
         assert!(ts.get_stdout_file() == "/usr/bin/a/b/c/def.out");
     }
 
     #[test]
     fn get_stderr_test() {
-        let ts = TestResult {
-            test_path: "/usr/bin/a/b/c/def.in".to_string(),
-            passed: false,
-            time: 0.0,
-            failed_cause: TestFail::InnerProblem("".to_string()),
-            return_code: 1,
-            compilation_warnings: None,
-        };
+        let ts = TestResult::new("/usr/bin/a/b/c/def.in");
 
         assert!(ts.get_stderr_file() == "/usr/bin/a/b/c/def.err");
     }
 
     #[test]
     fn get_test_path_test() {
-        let ts = TestResult {
-            test_path: "/usr/bin/a/b/c/def.in".to_string(),
-            passed: false,
-            time: 0.0,
-            failed_cause: TestFail::InnerProblem("".to_string()),
-            return_code: 1,
-            compilation_warnings: None,
-        };
+        let ts = TestResult::new("/usr/bin/a/b/c/def.in");
 
         assert!(ts.get_name() == "def.in");
     }
+
+    #[test]
+    fn warnings_match_test() {
+        let warnings = "a.c:3:9: warning: unused variable 'x' [-Wunused-variable]\n";
+
+        assert!(warnings_match(warnings, &[]));
+        assert!(warnings_match(warnings, &["-Wunused-variable".to_string()]));
+        assert!(!warnings_match(warnings, &["-Wsign-compare".to_string()]));
+        assert!(!warnings_match("a.c: note: something", &[]));
+    }
+
+    #[test]
+    fn retry_time_limit_test() {
+        let mut ts = TestResult::new("/usr/bin/a/b/c/def.in");
+        ts.set_time_limit(TimeLimit {
+            reference: 0.1,
+            factor: 2.0,
+        });
+        ts.retry(2, |attempt| {
+            attempt.usage = Some(Measurement {
+                wall: 0.5,
+                user: 0.5,
+                system: 0.0,
+                max_rss_kb: 1024,
+                timed_out: false,
+            });
+            attempt.passed = attempt.check_time_limit();
+        });
+
+        assert!(!ts.passed());
+        assert_eq!(ts.attempts, 3);
+        assert!(matches!(ts.get_failed_cause(), TestFail::TimeLimit(_)));
+    }
+
+    #[test]
+    fn expand_toolchain_test() {
+        let toolchain: Vec<String> = [
+            "rustc",
+            "{source}",
+            "-C",
+            "link-arg={program}",
+            "-o",
+            "{output}",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        assert_eq!(
+            expand_toolchain(&toolchain, "t.rs", "lib.o", "out"),
+            ["rustc", "t.rs", "-C", "link-arg=lib.o", "-o", "out"]
+        );
+    }
 }
@@ -0,0 +1,133 @@
+//! Time limits relative to runtime of the reference implementation. Reference is run on
+//! every test and its CPU time, multiplied by factor from settings, limits CPU time of the
+//! tested program. Runtimes of reference are cached by contents of reference executable
+//! and test input.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use super::build_cache::{hash_chunks, CACHE_DIR};
+use super::measure;
+use super::{Pipeline, TestResult};
+use crate::settings::Options;
+
+/// Number of runs of reference implementation, median of them is its runtime
+const REFERENCE_RUNS: usize = 3;
+/// Lowest CPU time limit (in seconds), so that very short runs are not failed by noise
+const MIN_LIMIT: f64 = 0.05;
+/// Wall time (in seconds) added to the deadline, as runs can wait for CPU under parallel load
+const KILL_SLACK: f64 = 1.0;
+
+/// Time limit of a single test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLimit {
+    /// CPU time of the reference implementation on the test, in seconds
+    pub reference: f64,
+    /// Multiple of reference time which tested program can use
+    pub factor: f64,
+}
+
+impl TimeLimit {
+    /// Returns limit of CPU time (user and system), in seconds
+    pub fn cpu(&self) -> f64 {
+        (self.reference * self.factor).max(MIN_LIMIT)
+    }
+
+    /// Returns wall time after which a run is killed, twice the CPU time limit with slack
+    pub fn deadline(&self) -> Duration {
+        Duration::from_secs_f64(2.0 * self.cpu() + KILL_SLACK)
+    }
+}
+
+fn times_path() -> String {
+    format!("{}/reference_cpu_times.json", CACHE_DIR)
+}
+
+/// Measures runtime (CPU time of the child only) of reference on given input
+fn measure_reference(reference: &[String], input: &[u8]) -> io::Result<f64> {
+    let mut times = Vec::with_capacity(REFERENCE_RUNS);
+    for _ in 0..REFERENCE_RUNS {
+        let (_, measurement) = measure::run_measured(reference, input)?;
+        times.push(measurement.cpu());
+    }
+    times.sort_by(f64::total_cmp);
+    Ok(times[REFERENCE_RUNS / 2])
+}
+
+/**
+Sets time limits of given tests, which are not run yet, to time limit factor from settings
+times runtime of the reference implementation on the same test. Does nothing when factor
+is 0 or in compiled mode, where tests are drivers compiled with the program, not its input.
+Runs with wrapper of given pipeline which is not measured (e.g. valgrind) are not limited,
+so the reference is not run then.
+*/
+pub fn set_time_limits<'a>(
+    results: impl IntoIterator<Item = &'a mut TestResult>,
+    settings: &Options,
+    pipeline: &Pipeline,
+) -> io::Result<()> {
+    let factor = settings.get_time_limit_factor();
+    if factor <= 0.0 || settings.get_program_mode() || !pipeline.measured() {
+        return Ok(());
+    }
+
+    let reference_path = settings.get_reference_path();
+    if !Path::new(reference_path).is_file() {
+        return Err(io::Error::other(
+            "reference implementation is needed for relative time limit",
+        ));
+    }
+    let reference = [reference_path.to_string()];
+    let reference_binary = fs::read(reference_path)?;
+
+    let mut times: BTreeMap<String, f64> = fs::read_to_string(times_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    for result in results {
+        let input = fs::read(result.get_test_path())?;
+        let key = format!("{:016x}", hash_chunks(&[&reference_binary, &input]));
+        let reference_time = match times.get(&key) {
+            Some(time) => *time,
+            None => {
+                let time = measure_reference(&reference, &input)?;
+                times.insert(key, time);
+                time
+            }
+        };
+
+        result.set_time_limit(TimeLimit {
+            reference: reference_time,
+            factor: factor as f64,
+        });
+    }
+
+    fs::create_dir_all(CACHE_DIR)?;
+    let content = serde_json::to_string_pretty(&times).map_err(io::Error::other)?;
+    fs::write(times_path(), content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_test() {
+        let limit = TimeLimit {
+            reference: 0.5,
+            factor: 3.0,
+        };
+        assert_eq!(limit.cpu(), 1.5);
+        assert_eq!(limit.deadline(), Duration::from_secs(4));
+
+        let short = TimeLimit {
+            reference: 0.0,
+            factor: 3.0,
+        };
+        assert_eq!(short.cpu(), MIN_LIMIT);
+    }
+}
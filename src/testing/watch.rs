@@ -0,0 +1,161 @@
+//! Watch mode: tests are run again whenever the program executable, the test folder
+//! or the sources folder (when set in settings) change. Changes are detected by polling
+//! modification times of files, bursts of changes are debounced into a single run.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use super::build_step::{self, BuildOutcome};
+use super::{Runner, TestResult};
+use crate::settings::Options;
+
+/// Time between checks of modification times
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+/// Time without further changes needed to start a run
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Modification times of watched files
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Events reported by watch mode to the interface
+pub enum WatchEvent<'a> {
+    /// Change was detected (or watching started), a new run begins
+    Started,
+    /// Build command from settings failed, tests were not run
+    BuildFailed(String),
+    /// Tests could not be loaded, with description of the error
+    LoadFailed(String),
+    /// Tests were run with given results
+    Finished(&'a [TestResult]),
+}
+
+/**
+Adds modification times of a file or of all files in a directory (recursively) to snapshot.
+Hidden files and temporary files of tests are skipped.
+*/
+fn collect(path: &Path, snapshot: &mut Snapshot) {
+    if path.is_dir() {
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with('.') && !name.starts_with("rtest_") {
+                collect(&entry.path(), snapshot);
+            }
+        }
+    } else if let Ok(time) = fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        snapshot.insert(path.to_path_buf(), time);
+    }
+}
+
+/// Takes modification times of program executable, tests and sources (if set)
+fn snapshot(settings: &Options) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    collect(Path::new(settings.get_program_path()), &mut snapshot);
+    collect(Path::new(settings.get_test_path()), &mut snapshot);
+    if !settings.get_sources_path().is_empty() {
+        collect(Path::new(settings.get_sources_path()), &mut snapshot);
+    }
+    snapshot
+}
+
+/**
+Waits until watched files differ from given snapshot and then stay unchanged
+for debounce time. Returns false when watching was stopped.
+*/
+fn wait_for_change(settings: &Options, last: &Snapshot, stop: &AtomicBool) -> bool {
+    let mut changed: Option<Snapshot> = None;
+    loop {
+        thread::sleep(if changed.is_some() {
+            DEBOUNCE
+        } else {
+            POLL_INTERVAL
+        });
+        if stop.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let current = snapshot(settings);
+        match changed {
+            Some(previous) if previous == current => return true,
+            _ if current != *last => changed = Some(current),
+            _ => changed = None,
+        }
+    }
+}
+
+/**
+Runs build step and tests, then waits for changes and runs them again, previously failed
+tests first. Every run is reported with given function. Returns when a line is entered
+on standard input.
+*/
+pub fn watch<F>(settings: &Options, mut report: F)
+where
+    F: FnMut(WatchEvent),
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_reader = Arc::clone(&stop);
+    thread::spawn(move || {
+        let mut line = String::new();
+        let _ = io::stdin().read_line(&mut line);
+        stop_reader.store(true, Ordering::SeqCst);
+    });
+
+    let mut failed: Vec<String> = Vec::new();
+    loop {
+        report(WatchEvent::Started);
+        match build_step::run_build_command(settings) {
+            BuildOutcome::Failed(cause) => report(WatchEvent::BuildFailed(cause)),
+            _ => match Runner::new(settings).failed_first(&failed).run() {
+                Ok(results) => {
+                    failed = results
+                        .iter()
+                        .filter(|result| !result.passed())
+                        .map(|result| result.get_test_path().to_string())
+                        .collect();
+                    report(WatchEvent::Finished(&results));
+                }
+                Err(e) => report(WatchEvent::LoadFailed(e.to_string())),
+            },
+        }
+
+        // Snapshot is taken after the run, so files written by build step do not trigger it again.
+        let last = snapshot(settings);
+        if !wait_for_change(settings, &last, &stop) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_test() {
+        let directory = std::env::temp_dir().join(format!("rtest_watch{}", std::process::id()));
+        fs::create_dir_all(directory.join("nested")).unwrap();
+        for file in ["a.in", "nested/b.in", ".hidden", "rtest_stdout0"] {
+            fs::write(directory.join(file), "").unwrap();
+        }
+
+        let mut snapshot = Snapshot::new();
+        collect(&directory, &mut snapshot);
+        fs::remove_dir_all(&directory).unwrap();
+
+        let files: Vec<_> = snapshot.keys().cloned().collect();
+        assert_eq!(
+            files,
+            vec![directory.join("a.in"), directory.join("nested/b.in")]
+        );
+    }
+}